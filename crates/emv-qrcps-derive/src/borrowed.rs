@@ -2,13 +2,13 @@ use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use syn::Ident;
 
-use crate::{field_is_option, field_is_stringy, ident_from_type, import_from_crate, FieldKind};
+use crate::{field_is_option, field_is_stringy, ident_from_type, import_from_crate, option_inner_ident, FieldKind};
 
 fn pre_tokenizer(field: &FieldKind) -> (TokenStream2, TokenStream2) {
     let name = &field.name;
     let id = &field.br_code_id;
 
-    let name_id = Ident::new(&*format!("{}_{}", field.name, field.br_code_id), field.name.span());
+    let name_id = Ident::new(&format!("{}_{}", field.name, field.br_code_id), field.name.span());
     let kind = &field.kind;
     let kind_ident = ident_from_type(&field.kind);
 
@@ -16,9 +16,12 @@ fn pre_tokenizer(field: &FieldKind) -> (TokenStream2, TokenStream2) {
     let is_stringy = field_is_stringy(kind);
 
     let pre = match (is_option, is_stringy) {
-        (true, false) => quote! {
-            let #name_id: Option<_> = #kind_ident::from_str(map.remove(#id));
-        },
+        (true, false) => {
+            let inner_ident = option_inner_ident(kind);
+            quote! {
+                let #name_id: Option<_> = map.remove(#id).map(#inner_ident::from_str);
+            }
+        }
         (false, false) => quote! {
             let #name_id = #kind_ident::from_str(map.remove(#id).unwrap());
         },
@@ -38,7 +41,7 @@ fn pre_tokenizer(field: &FieldKind) -> (TokenStream2, TokenStream2) {
 
 /// Generates the no alloc parser implementation.
 pub(crate) fn generate_parser_impl(struct_name: &Ident, fields: &[FieldKind]) -> TokenStream2 {
-    let (pre, pos): (Vec<_>, Vec<_>) = fields.iter().map(|field| pre_tokenizer(field)).unzip();
+    let (pre, pos): (Vec<_>, Vec<_>) = fields.iter().map(pre_tokenizer).unzip();
 
     // for custom struct types, we deserialize it first, with its own from_str
     // for options, we don't unwrap
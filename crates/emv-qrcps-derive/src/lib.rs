@@ -17,15 +17,12 @@ struct FieldKind {
     pub br_code_id: String,
     pub name: syn::Ident,
     pub(crate) kind: Type,
-    pub(crate) args: EncodeArgs,
 }
 
 #[derive(Debug, FromField)]
 #[darling(attributes(encoder))]
 struct EncodeArgs {
     id: String,
-    #[darling(default)]
-    default: Option<String>,
 }
 
 #[proc_macro_derive(EmvEncoder, attributes(encoder))]
@@ -50,12 +47,10 @@ pub fn derive_helper_attr(item: TokenStream) -> TokenStream {
         .iter()
         .map(|field| {
             let args = EncodeArgs::from_field(field).unwrap();
-            let code_id = args.id.clone();
             let ident = field.ident.as_ref().unwrap();
 
             FieldKind {
-                args,
-                br_code_id: code_id,
+                br_code_id: args.id,
                 name: ident.clone(),
                 kind: field.ty.clone(),
             }
@@ -63,7 +58,7 @@ pub fn derive_helper_attr(item: TokenStream) -> TokenStream {
         .collect();
 
     let push_output_tokens = field.iter().map(format_serializer_token).collect::<Vec<_>>();
-    let parser_impl = generate_parser_impl(struct_name, &*field);
+    let parser_impl = generate_parser_impl(struct_name, &field);
 
     let crc_fn_use_token = import_from_crate(quote! {helpers::calculate_crc16});
     let traits_token = import_from_crate(quote! {{Size, Encode}});
@@ -135,7 +130,7 @@ fn format_serializer_token(field_info: &FieldKind) -> TokenStream2 {
         (false, true) => {
             quote! {
                 #inner_token
-                if let Some(inner) = inner.as_deref() {
+                if let Some(inner) = inner.as_ref() {
                     let inner_serialized = inner.serialize();
                     output.push_str(&*format!("{}{:02}", #encoder_id, inner_serialized.len()));
                     output.push_str(&*inner_serialized);
@@ -167,15 +162,10 @@ fn get_struct_fields(derive_input: &syn::DeriveInput) -> Option<&StructFields> {
 
 fn is_string_from_arguments(arg: &GenericArgument) -> bool {
     match arg {
-        GenericArgument::Type(kind) => {
-            return match kind {
-                Type::Path(token) => match token.path.segments.first() {
-                    Some(p) => p.ident == "String" || p.ident == "Cow" || p.ident == "str",
-                    _ => false,
-                },
-                _ => false,
-            };
-        }
+        GenericArgument::Type(Type::Path(token)) => match token.path.segments.first() {
+            Some(p) => p.ident == "String" || p.ident == "Cow" || p.ident == "str",
+            _ => false,
+        },
         _ => false,
     }
 }
@@ -188,9 +178,7 @@ fn field_is_stringy(kind: &syn::Type) -> bool {
                 || path_segment.ident == "str"
                 || path_segment.ident == "Cow"
                 || match &path_segment.arguments {
-                    PathArguments::AngleBracketed(br) => {
-                        br.args.iter().any(|generic_arg| is_string_from_arguments(generic_arg))
-                    }
+                    PathArguments::AngleBracketed(br) => br.args.iter().any(is_string_from_arguments),
                     _ => false,
                 }
         }),
@@ -208,6 +196,20 @@ fn ident_from_type(kind: &syn::Type) -> Option<&Ident> {
     }
 }
 
+/// Returns the ident of `T` for a type of `Option<T>`.
+fn option_inner_ident(kind: &syn::Type) -> Option<&Ident> {
+    match kind {
+        syn::Type::Path(t) => match t.path.segments.first().map(|segment| &segment.arguments) {
+            Some(PathArguments::AngleBracketed(br)) => match br.args.first() {
+                Some(GenericArgument::Type(inner)) => ident_from_type(inner),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 fn field_is_option(kind: &syn::Type) -> bool {
     match kind {
         syn::Type::Path(t) => match t.path.segments.first() {
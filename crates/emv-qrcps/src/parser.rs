@@ -1,8 +1,6 @@
-use std::borrow::Cow;
 use std::collections::HashMap;
 use std::str::FromStr;
 
-use emv_qrcps_derive::EmvEncoder;
 use strum::IntoEnumIterator;
 use strum_macros::{EnumIter, IntoStaticStr};
 
@@ -14,16 +12,6 @@ pub enum HasChildren {
     AdditionalInformation,
 }
 
-#[derive(EmvEncoder, Clone, Debug, PartialEq)]
-struct MerchantAccountInformation<'a> {
-    #[encoder(id = "00")]
-    merchant_gui: Cow<'a, str>,
-    #[encoder(id = "01")]
-    /// Não deve conter o prefixo de procolo, ex: http.
-    /// Acesso deve ser após validações, e exclusivamente em HTTPS.
-    merchant_url: Cow<'a, str>,
-}
-
 pub trait Parsed<'a> {
     fn from_lookup(map: &mut HashMap<&str, &'a str>) -> Self;
 }
@@ -72,7 +60,19 @@ pub fn header_length_remaining(pix_string: &str) -> Option<(&str, &str, &str)> {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use std::borrow::Cow;
+
+    use emv_qrcps_derive::EmvEncoder;
+
+    #[derive(EmvEncoder, Clone, Debug, PartialEq)]
+    struct MerchantAccountInformation<'a> {
+        #[encoder(id = "00")]
+        merchant_gui: Cow<'a, str>,
+        #[encoder(id = "01")]
+        /// Não deve conter o prefixo de procolo, ex: http.
+        /// Acesso deve ser após validações, e exclusivamente em HTTPS.
+        merchant_url: Cow<'a, str>,
+    }
 
     fn sample_merchant() -> &'static str {
         "0028123e4567-e12b-12d1-a456-42720102oi"
@@ -88,24 +88,6 @@ mod tests {
         assert_eq!(basic, MerchantAccountInformation::from_str(sample_merchant()));
     }
 
-    #[allow(dead_code)]
-    fn bacen_static_sample() -> &'static str {
-        "00020126580014br.gov.bcb.pix0136123e4567-e12b-12d1-a456-4266554400005204000053039865802BR5913Fulano de \
-         Tal6008BRASILIA62070503***63041D3D"
-    }
-
-    #[allow(dead_code)]
-    fn bacen_dynamic_sample() -> &'static str {
-        "00020101021226700014br.gov.bcb.pix2548pix.example.com/\
-         8b3da2f39a4140d1a91abd93113bd4415204000053039865802BR5913Fulano de Tal6008BRASILIA62070503***630464E4"
-    }
-
-    #[allow(dead_code)]
-    fn generated_sample() -> &'static str {
-        "00020126530014br.gov.bcb.pix0119saskenuba@gmail.com0208[Pix.ae]520400005303986540550.\
-         005802BR5903Pix6003Pix62070503***63048287"
-    }
-
     #[derive(EmvEncoder, Debug, Clone)]
     struct SampleBrCode<'a> {
         #[encoder(id = "00")]
@@ -0,0 +1,134 @@
+//! Synchronous execution of requests, for applications without an async runtime.
+//!
+//! [`BlockingPixClient`] wraps a `PixClient`, sharing all of its endpoint groups, and executes their requests on a
+//! single-threaded runtime that it owns. The `PixClient` itself never owns a runtime, so it can still be freely used
+//! and dropped within async code.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use pix_api_client::blocking::BlockingPixClient;
+//! use pix_api_client::cob::{CobrancaImediata, Devedor};
+//! use pix_api_client::PixClient;
+//!
+//! # fn doc_test(pix_client: PixClient) -> Result<(), anyhow::Error> {
+//! let pix_client = BlockingPixClient::new(pix_client)?;
+//!
+//! let devedor = Devedor::new_pessoa_fisica("00000000000".to_string(), "Fulano de tal".to_string());
+//! let payload = CobrancaImediata::new(10.25, "my-key".to_string(), devedor);
+//!
+//! let response: CobrancaImediata = pix_client.execute(pix_client.cob().criar_cobranca_imediata(payload))?;
+//! # Ok(())
+//! # }
+//! ```
+use std::ops::Deref;
+
+use serde::de::DeserializeOwned;
+use tokio::runtime::{Builder, Handle, Runtime};
+
+use crate::errors::{ApiResult, PixError};
+use crate::{ApiRequest, Executor, PixClient};
+
+#[derive(Debug)]
+pub struct BlockingPixClient {
+    inner: PixClient,
+    /// Always `Some`, until it is shut down on drop.
+    runtime: Option<Runtime>,
+}
+
+impl BlockingPixClient {
+    pub fn new(client: PixClient) -> ApiResult<BlockingPixClient> {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| PixError::Other(format!("Could not start the blocking runtime: {}", e)))?;
+
+        Ok(Self {
+            inner: client,
+            runtime: Some(runtime),
+        })
+    }
+
+    /// Executes `request`, created by one of the endpoint groups of this client, blocking the current thread.
+    ///
+    /// Fails when called from within an async runtime, where blocking would stall its other tasks.
+    pub fn execute<T>(&self, request: ApiRequest<'_, T>) -> ApiResult<T>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        if Handle::try_current().is_ok() {
+            return Err(PixError::Other(
+                "Blocking requests can't be executed from within an async runtime".to_string(),
+            ));
+        }
+
+        let runtime = self.runtime.as_ref().expect("the runtime is only taken on drop");
+        runtime.block_on(request.execute())
+    }
+}
+
+impl Deref for BlockingPixClient {
+    type Target = PixClient;
+
+    fn deref(&self) -> &PixClient {
+        &self.inner
+    }
+}
+
+impl Drop for BlockingPixClient {
+    /// Unlike dropping the runtime, shutting it down in the background doesn't panic within an async runtime.
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderMap;
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    #[test]
+    fn t_blocking_execute() {
+        let server_runtime = Runtime::new().unwrap();
+        let server = server_runtime.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/loc/7768"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "id": 7768,
+                    "location": "pix.example.com/qr/v2/cobv/9d36b84fc70b478fb95c12729b90ca25",
+                    "tipoCob": "cobv",
+                    "criacao": "2020-03-11T21:19:51.013Z"
+                })))
+                .expect(2)
+                .mount(&server)
+                .await;
+            server
+        });
+
+        let pix_client = PixClient::from_client(reqwest::Client::new(), &server.uri(), HeaderMap::new());
+        let client = BlockingPixClient::new(pix_client).unwrap();
+        for _ in 0..2 {
+            let location = client.execute(client.loc().consultar(7768)).unwrap();
+            assert_eq!(location.id, 7768);
+        }
+
+        // dropping the client within an async runtime must not panic
+        server_runtime.block_on(async move { drop(client) });
+    }
+
+    #[tokio::test]
+    async fn t_blocking_execute_within_async_runtime() {
+        let pix_client = PixClient::from_client(reqwest::Client::new(), "http://localhost", HeaderMap::new());
+        let client = BlockingPixClient::new(pix_client).unwrap();
+
+        let result = client.execute(client.loc().consultar(7768));
+        assert!(matches!(result, Err(PixError::Other(_))));
+    }
+}
@@ -0,0 +1,308 @@
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Certificate, Client, Identity, Proxy};
+
+use crate::errors::{ApiResult, PixError};
+use crate::oauth::TokenManager;
+use crate::rate_limit::RateLimiter;
+use crate::retry::RetryPolicy;
+use crate::PixClient;
+
+/// Builds a `PixClient`, with full control over its TLS configuration.
+///
+/// PSP's authenticate clients with mutual TLS, so an identity is usually mandatory.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::fs;
+/// # use std::time::Duration;
+/// use pix_api_client::oauth::TokenManager;
+/// use pix_api_client::PixClient;
+///
+/// # fn teste() -> Result<(), anyhow::Error> {
+/// let pix_client = PixClient::builder("https://my-pix-h")
+///     .pem_identity(fs::read("my_cert.pem")?, fs::read("my_key.pem")?)
+///     .root_certificate_pem(fs::read("psp_ca.pem")?)
+///     .timeout(Duration::from_secs(30))
+///     .token_manager(TokenManager::basic("my-id", "my-secret"))
+///     .build()?;
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct PixClientBuilder {
+    endpoint: String,
+    identity: Option<IdentitySource>,
+    root_certificates: Vec<CertificateSource>,
+    headers: HeaderMap,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    proxy: Option<Proxy>,
+    user_agent: Option<String>,
+    client: Option<Client>,
+    token_manager: Option<TokenManager>,
+    retry_policy: Option<RetryPolicy>,
+    rate_limiter: Option<RateLimiter>,
+    https_only: bool,
+}
+
+enum IdentitySource {
+    Pkcs12 { der: Vec<u8>, password: String },
+    Pem { certificate: Vec<u8>, key: Vec<u8> },
+}
+
+// Never print the private key.
+impl std::fmt::Debug for IdentitySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdentitySource::Pkcs12 { .. } => f.write_str("Pkcs12"),
+            IdentitySource::Pem { .. } => f.write_str("Pem"),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum CertificateSource {
+    Pem(Vec<u8>),
+    Der(Vec<u8>),
+}
+
+impl PixClient {
+    pub fn builder(endpoint: &str) -> PixClientBuilder {
+        PixClientBuilder::new(endpoint)
+    }
+}
+
+impl PixClientBuilder {
+    pub fn new(endpoint: &str) -> PixClientBuilder {
+        Self {
+            endpoint: endpoint.to_string(),
+            identity: None,
+            root_certificates: Vec::new(),
+            headers: HeaderMap::new(),
+            timeout: None,
+            connect_timeout: None,
+            proxy: None,
+            user_agent: None,
+            client: None,
+            token_manager: None,
+            retry_policy: None,
+            rate_limiter: None,
+            https_only: true,
+        }
+    }
+
+    /// Client certificate and private key, bundled as a PKCS#12 DER archive.
+    pub fn pkcs12_identity(mut self, der: Vec<u8>, password: &str) -> PixClientBuilder {
+        self.identity = Some(IdentitySource::Pkcs12 {
+            der,
+            password: password.to_string(),
+        });
+        self
+    }
+
+    /// Client certificate, and its PKCS#8 private key, both PEM encoded.
+    pub fn pem_identity(mut self, certificate: Vec<u8>, key: Vec<u8>) -> PixClientBuilder {
+        self.identity = Some(IdentitySource::Pem { certificate, key });
+        self
+    }
+
+    /// Trusts an additional root certificate, PEM encoded. Useful for PSP's homologation environments.
+    pub fn root_certificate_pem(mut self, certificate: Vec<u8>) -> PixClientBuilder {
+        self.root_certificates.push(CertificateSource::Pem(certificate));
+        self
+    }
+
+    /// Trusts an additional root certificate, DER encoded.
+    pub fn root_certificate_der(mut self, certificate: Vec<u8>) -> PixClientBuilder {
+        self.root_certificates.push(CertificateSource::Der(certificate));
+        self
+    }
+
+    /// Header sent on every request. An `Authorization` header can still be replaced later by
+    /// `PixClient::swap_authorization_token`.
+    pub fn header(mut self, key: HeaderName, value: HeaderValue) -> PixClientBuilder {
+        self.headers.insert(key, value);
+        self
+    }
+
+    pub fn headers(mut self, headers: HeaderMap) -> PixClientBuilder {
+        self.headers.extend(headers);
+        self
+    }
+
+    /// Timeout of the whole request, from connecting until the response body is read.
+    pub fn timeout(mut self, timeout: Duration) -> PixClientBuilder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> PixClientBuilder {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    pub fn proxy(mut self, proxy: Proxy) -> PixClientBuilder {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: &str) -> PixClientBuilder {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Uses an already configured `reqwest::Client`. TLS, timeout, proxy, user agent and `https_only` must be set on
+    /// the given client instead, and [`build`](Self::build) fails when any of them is also set on this builder.
+    pub fn client(mut self, client: Client) -> PixClientBuilder {
+        self.client = Some(client);
+        self
+    }
+
+    pub fn token_manager(mut self, token_manager: TokenManager) -> PixClientBuilder {
+        self.token_manager = Some(token_manager);
+        self
+    }
+
+    /// Defaults to [`RetryPolicy::default`].
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> PixClientBuilder {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> PixClientBuilder {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Refuses plain HTTP urls. Defaults to `true`, and should only be disabled when testing against a local server,
+    /// such as the `MockPsp` of the `mock-psp` feature.
+    pub fn https_only(mut self, https_only: bool) -> PixClientBuilder {
+        self.https_only = https_only;
+        self
+    }
+
+    pub fn build(self) -> ApiResult<PixClient> {
+        let client = match self.client {
+            Some(_) if self.has_client_options() => {
+                return Err(PixError::Other(
+                    "TLS, timeout, proxy, user agent and https_only options can't be combined with a custom client"
+                        .to_string(),
+                ))
+            }
+            Some(client) => client,
+            None => {
+                let mut builder = Client::builder().https_only(self.https_only);
+
+                if let Some(identity) = self.identity {
+                    let identity = match identity {
+                        IdentitySource::Pkcs12 { der, password } => Identity::from_pkcs12_der(&der, &password),
+                        IdentitySource::Pem { certificate, key } => Identity::from_pkcs8_pem(&certificate, &key),
+                    };
+                    builder = builder.identity(identity.map_err(PixError::InvalidCertificate)?);
+                }
+
+                for certificate in self.root_certificates {
+                    let certificate = match certificate {
+                        CertificateSource::Pem(pem) => Certificate::from_pem(&pem),
+                        CertificateSource::Der(der) => Certificate::from_der(&der),
+                    };
+                    builder = builder.add_root_certificate(certificate.map_err(PixError::InvalidCertificate)?);
+                }
+
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(connect_timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(connect_timeout);
+                }
+                if let Some(proxy) = self.proxy {
+                    builder = builder.proxy(proxy);
+                }
+                if let Some(user_agent) = self.user_agent {
+                    builder = builder.user_agent(user_agent);
+                }
+
+                builder.build()?
+            }
+        };
+
+        let mut pix_client = PixClient::from_client(client, &self.endpoint, self.headers);
+        pix_client.token_manager = self.token_manager;
+        pix_client.rate_limiter = self.rate_limiter;
+        if let Some(retry_policy) = self.retry_policy {
+            pix_client.retry_policy = retry_policy;
+        }
+
+        Ok(pix_client)
+    }
+
+    /// Whether any option that is applied to the `reqwest::Client` built by this builder was set.
+    fn has_client_options(&self) -> bool {
+        self.identity.is_some()
+            || !self.root_certificates.is_empty()
+            || self.timeout.is_some()
+            || self.connect_timeout.is_some()
+            || self.proxy.is_some()
+            || self.user_agent.is_some()
+            || !self.https_only
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::AUTHORIZATION;
+
+    #[test]
+    fn t_invalid_certificate_is_an_error() {
+        let result = PixClient::builder("https://pix.example.com")
+            .pkcs12_identity(b"not a certificate".to_vec(), "password")
+            .build();
+
+        assert!(matches!(result, Err(PixError::InvalidCertificate(_))));
+    }
+
+    #[test]
+    fn t_custom_client_rejects_client_options() {
+        let result = PixClient::builder("https://pix.example.com")
+            .client(Client::new())
+            .timeout(Duration::from_secs(10))
+            .build();
+        assert!(matches!(result, Err(PixError::Other(_))));
+
+        let result = PixClient::builder("http://localhost")
+            .client(Client::new())
+            .https_only(false)
+            .build();
+        assert!(matches!(result, Err(PixError::Other(_))));
+
+        let pix_client = PixClient::builder("https://pix.example.com")
+            .client(Client::new())
+            .header(AUTHORIZATION, HeaderValue::from_static("Basic bXktaWQ6bXktc2VjcmV0"))
+            .build();
+        assert!(pix_client.is_ok());
+    }
+
+    #[test]
+    fn t_pem_identity() {
+        let certificate = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+
+        let pix_client = PixClient::builder("https://pix.example.com")
+            .pem_identity(
+                certificate.serialize_pem().unwrap().into_bytes(),
+                certificate.serialize_private_key_pem().into_bytes(),
+            )
+            .root_certificate_pem(certificate.serialize_pem().unwrap().into_bytes())
+            .header(AUTHORIZATION, HeaderValue::from_static("Basic bXktaWQ6bXktc2VjcmV0"))
+            .timeout(Duration::from_secs(10))
+            .user_agent("my-erp/1.0")
+            .build()
+            .unwrap();
+
+        assert!(pix_client.headers.is_empty());
+        assert!(pix_client.authorization.load().is_some());
+    }
+}
@@ -1,7 +1,9 @@
-use reqwest::{Method, RequestBuilder};
+use chrono::{DateTime, Utc};
+use reqwest::Method;
 use serde::{Deserialize, Serialize};
 
-use crate::{ApiRequest, PixClient};
+use crate::paginacao::{PaginacaoParams, Paginado, Parametros};
+use crate::{ApiRequest, CobTxid, PixClient};
 
 pub struct CobEndpoint<'a> {
     inner: &'a PixClient,
@@ -32,12 +34,16 @@ pub struct CobrancaImediata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub txid: Option<String>,
 
+    /// Revisão da cobrança, incrementada a cada alteração. Exclusivo como resposta.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revisao: Option<i32>,
+
     #[serde(rename = "loc")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub location: Option<Location>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub status: Option<String>,
+    pub status: Option<CobrancaStatus>,
 
     /// Campo que será para que o pagador desta cobrança insira uma informação.
     /// Sua implementação depende do PSP do pagador. Não é garantido seu preenchimento. Verifique.
@@ -60,6 +66,7 @@ impl CobrancaImediata {
             valor,
             chave_pix_recebedor,
             txid: None,
+            revisao: None,
             location: None,
             status: None,
             solicitacao_pagador: None,
@@ -138,12 +145,26 @@ impl Valor {
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Location {
-    id: i64,
+    pub id: i64,
     #[serde(rename = "location")]
     pub url: String,
     #[serde(rename = "tipoCob")]
-    tipo_cob: String,
-    criacao: String,
+    pub tipo_cob: TipoCob,
+    pub criacao: String,
+    /// Presente somente quando a location está vinculada a uma cobrança.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txid: Option<String>,
+}
+
+/// Tipo da cobrança que pode ser vinculada a uma location.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TipoCob {
+    /// Cobrança imediata
+    #[default]
+    Cob,
+    /// Cobrança com vencimento
+    Cobv,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -152,43 +173,214 @@ pub struct InfoAdicionais {
     pub valor: String,
 }
 
+/// Revisão de uma cobrança imediata. Somente os campos informados são alterados.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevisaoCobranca {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calendario: Option<Calendario>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub devedor: Option<Devedor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valor: Option<Valor>,
+    #[serde(rename = "chave")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chave_pix_recebedor: Option<String>,
+    /// Somente `REMOVIDA_PELO_USUARIO_RECEBEDOR` é aceito pelos PSP's.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<CobrancaStatus>,
+    #[serde(rename = "solicitacaoPagador")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub solicitacao_pagador: Option<String>,
+    #[serde(rename = "infoAdicionais")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info_adicionais: Option<Vec<InfoAdicionais>>,
+}
+
+impl RevisaoCobranca {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Segundos para a expiração da cobrança, a partir de sua criação.
+    pub fn expiracao(mut self, expiracao: i64) -> Self {
+        self.calendario = Some(Calendario {
+            expiracao,
+            ..Default::default()
+        });
+        self
+    }
+
+    pub fn devedor(mut self, devedor: Devedor) -> Self {
+        self.devedor = Some(devedor);
+        self
+    }
+
+    pub fn valor(mut self, valor: Valor) -> Self {
+        self.valor = Some(valor);
+        self
+    }
+
+    pub fn chave_pix_recebedor(mut self, chave_pix_recebedor: String) -> Self {
+        self.chave_pix_recebedor = Some(chave_pix_recebedor);
+        self
+    }
+
+    pub fn status(mut self, status: CobrancaStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn solicitacao_pagador(mut self, solicitacao_pagador: String) -> Self {
+        self.solicitacao_pagador = Some(solicitacao_pagador);
+        self
+    }
+
+    pub fn info_adicionais(mut self, info_adicionais: Vec<InfoAdicionais>) -> Self {
+        self.info_adicionais = Some(info_adicionais);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CobrancasImediatas {
+    pub parametros: Parametros,
+    pub cobs: Vec<CobrancaImediata>,
+}
+
+impl Paginado for CobrancasImediatas {
+    type Item = CobrancaImediata;
+
+    fn parametros(&self) -> &Parametros {
+        &self.parametros
+    }
+
+    fn into_items(self) -> Vec<CobrancaImediata> {
+        self.cobs
+    }
+}
+
+/// Filtros aceitos pela listagem de cobranças imediatas.
+///
+/// Os campos `cpf` e `cnpj` são mutuamente exclusivos, e o último informado prevalece.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CobFiltro {
+    pub inicio: DateTime<Utc>,
+    pub fim: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpf: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cnpj: Option<String>,
+    #[serde(rename = "locationPresente")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location_presente: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<CobrancaStatus>,
+    #[serde(flatten)]
+    pub paginacao: PaginacaoParams,
+}
+
+impl CobFiltro {
+    pub fn new(inicio: DateTime<Utc>, fim: DateTime<Utc>) -> Self {
+        Self {
+            inicio,
+            fim,
+            cpf: None,
+            cnpj: None,
+            location_presente: None,
+            status: None,
+            paginacao: Default::default(),
+        }
+    }
+
+    pub fn cpf(mut self, cpf: String) -> Self {
+        self.cpf = Some(cpf);
+        self.cnpj = None;
+        self
+    }
+
+    pub fn cnpj(mut self, cnpj: String) -> Self {
+        self.cnpj = Some(cnpj);
+        self.cpf = None;
+        self
+    }
+
+    pub fn location_presente(mut self, location_presente: bool) -> Self {
+        self.location_presente = Some(location_presente);
+        self
+    }
+
+    pub fn status(mut self, status: CobrancaStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn paginacao(mut self, paginacao: PaginacaoParams) -> Self {
+        self.paginacao = paginacao;
+        self
+    }
+}
+
 impl PixClient {
-    pub fn cob(&self) -> CobEndpoint {
+    pub fn cob(&self) -> CobEndpoint<'_> {
         CobEndpoint { inner: self }
     }
 }
 
 impl<'a> CobEndpoint<'a> {
-    pub fn criar_cobranca_txid(&self, txid: String, payload: CobrancaImediata) -> ApiRequest<CobrancaImediata> {
-        let endpoint = format!("{}/cob/{}", &*self.inner.base_endpoint, txid);
-        self.inner.request_with_headers(Method::PUT, &*endpoint, payload)
+    /// Cria uma cobrança imediata com o `txid` informado, que pode ser gerado com [`CobTxid::random`] ou
+    /// [`CobTxid::from_reference`].
+    pub fn criar_cobranca_txid(&self, txid: &CobTxid, payload: CobrancaImediata) -> ApiRequest<'a, CobrancaImediata> {
+        let endpoint = format!("{}/cob/{}", self.inner.base_endpoint, txid);
+        self.inner.request_with_headers(Method::PUT, &endpoint, payload)
+    }
+
+    /// Consulta a cobrança `txid`, em sua revisão mais recente, ou na `revisao` informada.
+    pub fn consultar_cobranca_txid(&self, txid: &CobTxid, revisao: Option<i32>) -> ApiRequest<'a, CobrancaImediata> {
+        let endpoint = format!("{}/cob/{}", self.inner.base_endpoint, txid);
+        let query = revisao.map(|revisao| [("revisao", revisao)]);
+        self.inner.request_with_query(Method::GET, &endpoint, query)
     }
 
-    pub fn consultar_cobranca_txid(&self, txid: String, payload: CobrancaImediata) -> ApiRequest<CobrancaImediata> {
-        let endpoint = format!("{}/cob/{}", &*self.inner.base_endpoint, txid);
-        self.inner.request_with_headers(Method::GET, &*endpoint, payload)
+    /// Altera somente os campos informados em `revisao`, retornando a cobrança revisada.
+    pub fn revisar_cobranca_txid(&self, txid: &CobTxid, revisao: RevisaoCobranca) -> ApiRequest<'a, CobrancaImediata> {
+        let endpoint = format!("{}/cob/{}", self.inner.base_endpoint, txid);
+        self.inner.request_with_headers(Method::PATCH, &endpoint, revisao)
     }
-    pub fn revisar_cobranca_txid(&self, txid: String, payload: CobrancaImediata) -> ApiRequest<CobrancaImediata> {
-        let endpoint = format!("{}/cob/{}", &*self.inner.base_endpoint, txid);
-        self.inner.request_with_headers(Method::POST, &*endpoint, payload)
+
+    /// Remove a cobrança, que passa a ter o status `REMOVIDA_PELO_USUARIO_RECEBEDOR` e não pode mais ser paga.
+    pub fn cancelar(&self, txid: &CobTxid) -> ApiRequest<'a, CobrancaImediata> {
+        let revisao = RevisaoCobranca::new().status(CobrancaStatus::REMOVIDA_PELO_USUARIO_RECEBEDOR);
+        self.revisar_cobranca_txid(txid, revisao)
+    }
+
+    /// Altera o valor original de uma cobrança ainda ativa.
+    pub fn alterar_valor(&self, txid: &CobTxid, valor: f64) -> ApiRequest<'a, CobrancaImediata> {
+        let revisao = RevisaoCobranca::new().valor(Valor::new(valor, false));
+        self.revisar_cobranca_txid(txid, revisao)
     }
 
     /// Criar uma cobrança imediata.
     /// Diferente de `criar_cobranca_imediata`, o `txid` é definido pelo PSP.
-    pub fn criar_cobranca_imediata(&self, payload: CobrancaImediata) -> ApiRequest<CobrancaImediata> {
-        let endpoint = format!("{}/cob", &*self.inner.base_endpoint);
-        self.inner.request_with_headers(Method::POST, &*endpoint, payload)
+    pub fn criar_cobranca_imediata(&self, payload: CobrancaImediata) -> ApiRequest<'a, CobrancaImediata> {
+        let endpoint = format!("{}/cob", self.inner.base_endpoint);
+        self.inner.request_with_headers(Method::POST, &endpoint, payload)
     }
 
-    pub fn consultar_cobrancas(&self) -> RequestBuilder {
-        let endpoint = format!("{}/cob", &*self.inner.base_endpoint);
-        self.inner.inner_client.request(Method::GET, &endpoint)
+    pub fn consultar_cobrancas(&self, filtro: CobFiltro) -> ApiRequest<'a, CobrancasImediatas> {
+        let endpoint = format!("{}/cob", self.inner.base_endpoint);
+        self.inner.request_with_query(Method::GET, &endpoint, Some(filtro))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use serde_json::json;
+    use wiremock::matchers::{body_json, method, path, query_param};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
     use super::*;
+    use crate::header::HeaderMap;
+    use crate::Executor;
 
     #[test]
     fn t_value() {
@@ -198,4 +390,76 @@ mod tests {
         let new_integer_value = Valor::new(400f64, false);
         println!(": {:?}", new_integer_value);
     }
+
+    #[test]
+    fn t_revisao_somente_campos_alterados() {
+        let revisao = RevisaoCobranca::new().expiracao(7200).valor(Valor::new(20.5, false));
+
+        assert_eq!(
+            serde_json::to_value(revisao).unwrap(),
+            json!({ "calendario": { "expiracao": 7200 }, "valor": { "original": "20.50" } })
+        );
+    }
+
+    fn cobranca_response(status: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "calendario": { "criacao": "2020-09-09T20:15:00.358Z", "expiracao": 3600 },
+            "txid": "7978c0c97ea847e78e8849634473c1f1",
+            "revisao": 1,
+            "status": status,
+            "devedor": { "cpf": "12345678909", "nome": "Francisco da Silva" },
+            "valor": { "original": "567.89" },
+            "chave": "a1f4102e-a446-4a57-bcce-6fa48899c1d1"
+        }))
+    }
+
+    #[tokio::test]
+    async fn t_consultar_sem_corpo() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/cob/7978c0c97ea847e78e8849634473c1f1"))
+            .and(query_param("revisao", "1"))
+            .and(|request: &Request| request.body.is_empty())
+            .respond_with(cobranca_response("ATIVA"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/cob/7978c0c97ea847e78e8849634473c1f1"))
+            .and(|request: &Request| request.url.query().is_none() && request.body.is_empty())
+            .respond_with(cobranca_response("ATIVA"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = PixClient::from_client(reqwest::Client::new(), &server.uri(), HeaderMap::new());
+        let cob = client.cob();
+        let txid = CobTxid::new("7978c0c97ea847e78e8849634473c1f1").unwrap();
+
+        let revisada = cob.consultar_cobranca_txid(&txid, Some(1)).execute().await.unwrap();
+        assert_eq!(revisada.revisao, Some(1));
+        cob.consultar_cobranca_txid(&txid, None).execute().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn t_cancelar() {
+        let server = MockServer::start().await;
+        Mock::given(method("PATCH"))
+            .and(path("/cob/7978c0c97ea847e78e8849634473c1f1"))
+            .and(body_json(json!({ "status": "REMOVIDA_PELO_USUARIO_RECEBEDOR" })))
+            .respond_with(cobranca_response("REMOVIDA_PELO_USUARIO_RECEBEDOR"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = PixClient::from_client(reqwest::Client::new(), &server.uri(), HeaderMap::new());
+        let cobranca = client
+            .cob()
+            .cancelar(&CobTxid::new("7978c0c97ea847e78e8849634473c1f1").unwrap())
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(cobranca.status, Some(CobrancaStatus::REMOVIDA_PELO_USUARIO_RECEBEDOR));
+    }
 }
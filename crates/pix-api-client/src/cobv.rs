@@ -0,0 +1,296 @@
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use crate::cob::{CobrancaStatus, Devedor, InfoAdicionais, Location};
+use crate::paginacao::{PaginacaoParams, Paginado, Parametros};
+use crate::{ApiRequest, CobTxid, PixClient};
+
+pub struct CobvEndpoint<'a> {
+    inner: &'a PixClient,
+}
+
+impl PixClient {
+    pub fn cobv(&self) -> CobvEndpoint<'_> {
+        CobvEndpoint { inner: self }
+    }
+}
+
+/// Cobrança com vencimento, utilizada para boletos e faturas pagáveis via Pix.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CobrancaComVencimento {
+    pub calendario: CalendarioVencimento,
+
+    /// Id da Transação. Obrigatório quando a cobrança faz parte de um lote.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txid: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revisao: Option<i32>,
+
+    #[serde(rename = "loc")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<CobrancaStatus>,
+
+    pub devedor: DevedorVencimento,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recebedor: Option<Recebedor>,
+
+    pub valor: ValorVencimento,
+
+    /// Campo da chave PIX do recebedor desta cobrança.
+    #[serde(rename = "chave")]
+    pub chave_pix_recebedor: String,
+
+    #[serde(rename = "solicitacaoPagador")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub solicitacao_pagador: Option<String>,
+
+    #[serde(rename = "infoAdicionais")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info_adicionais: Option<Vec<InfoAdicionais>>,
+}
+
+impl CobrancaComVencimento {
+    /// Creates a new due-date charge, with `data_de_vencimento` formatted as `YYYY-MM-DD`.
+    pub fn new(
+        txid: CobTxid,
+        data_de_vencimento: String,
+        valor: f64,
+        chave_pix_recebedor: String,
+        devedor: Devedor,
+    ) -> CobrancaComVencimento {
+        Self {
+            calendario: CalendarioVencimento::new(data_de_vencimento),
+            txid: Some(txid.into()),
+            devedor: DevedorVencimento::new(devedor),
+            valor: ValorVencimento::new(valor),
+            chave_pix_recebedor,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarioVencimento {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub criacao: Option<String>,
+
+    /// Data de vencimento da cobrança, no formato `YYYY-MM-DD`.
+    #[serde(rename = "dataDeVencimento")]
+    pub data_de_vencimento: String,
+
+    /// Quantidade de dias corridos após o vencimento em que a cobrança ainda poderá ser paga.
+    #[serde(rename = "validadeAposVencimento")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validade_apos_vencimento: Option<i32>,
+}
+
+impl CalendarioVencimento {
+    pub fn new(data_de_vencimento: String) -> Self {
+        Self {
+            criacao: None,
+            data_de_vencimento,
+            validade_apos_vencimento: None,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DevedorVencimento {
+    #[serde(flatten)]
+    pub devedor: Devedor,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logradouro: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cidade: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uf: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cep: Option<String>,
+}
+
+impl DevedorVencimento {
+    pub fn new(devedor: Devedor) -> Self {
+        Self {
+            devedor,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recebedor {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cnpj: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nome: Option<String>,
+    #[serde(rename = "nomeFantasia")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nome_fantasia: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logradouro: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cidade: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uf: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cep: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValorVencimento {
+    pub original: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multa: Option<Modalidade>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub juros: Option<Modalidade>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub abatimento: Option<Modalidade>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desconto: Option<Desconto>,
+}
+
+impl ValorVencimento {
+    // Valor com no máximo duas casas decimais. Caso houver mais que 2 casas, o valor será truncado.
+    pub fn new(valor_original: f64) -> ValorVencimento {
+        Self {
+            original: format!("{:.2}", valor_original),
+            ..Default::default()
+        }
+    }
+}
+
+/// Utilizado por multa, juros e abatimento. O significado de `modalidade` varia entre cada um deles, conforme a
+/// especificação do Bacen.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Modalidade {
+    pub modalidade: i32,
+    #[serde(rename = "valorPerc")]
+    pub valor_perc: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Desconto {
+    pub modalidade: i32,
+    #[serde(rename = "valorPerc")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valor_perc: Option<String>,
+    #[serde(rename = "descontoDataFixa")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desconto_data_fixa: Option<Vec<DescontoDataFixa>>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DescontoDataFixa {
+    pub data: String,
+    #[serde(rename = "valorPerc")]
+    pub valor_perc: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CobrancasComVencimento {
+    pub parametros: Parametros,
+    pub cobs: Vec<CobrancaComVencimento>,
+}
+
+impl Paginado for CobrancasComVencimento {
+    type Item = CobrancaComVencimento;
+
+    fn parametros(&self) -> &Parametros {
+        &self.parametros
+    }
+
+    fn into_items(self) -> Vec<CobrancaComVencimento> {
+        self.cobs
+    }
+}
+
+/// Filtros aceitos pela listagem de cobranças com vencimento.
+///
+/// Os campos `cpf` e `cnpj` são mutuamente exclusivos, e o último informado prevalece.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CobvFiltro {
+    pub inicio: DateTime<Utc>,
+    pub fim: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpf: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cnpj: Option<String>,
+    #[serde(rename = "locationPresente")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location_presente: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<CobrancaStatus>,
+    #[serde(flatten)]
+    pub paginacao: PaginacaoParams,
+}
+
+impl CobvFiltro {
+    pub fn new(inicio: DateTime<Utc>, fim: DateTime<Utc>) -> Self {
+        Self {
+            inicio,
+            fim,
+            cpf: None,
+            cnpj: None,
+            location_presente: None,
+            status: None,
+            paginacao: Default::default(),
+        }
+    }
+
+    pub fn cpf(mut self, cpf: String) -> Self {
+        self.cpf = Some(cpf);
+        self.cnpj = None;
+        self
+    }
+
+    pub fn cnpj(mut self, cnpj: String) -> Self {
+        self.cnpj = Some(cnpj);
+        self.cpf = None;
+        self
+    }
+
+    pub fn location_presente(mut self, location_presente: bool) -> Self {
+        self.location_presente = Some(location_presente);
+        self
+    }
+
+    pub fn status(mut self, status: CobrancaStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn paginacao(mut self, paginacao: PaginacaoParams) -> Self {
+        self.paginacao = paginacao;
+        self
+    }
+}
+
+impl<'a> CobvEndpoint<'a> {
+    pub fn criar_cobranca_txid(
+        &self,
+        txid: &CobTxid,
+        payload: CobrancaComVencimento,
+    ) -> ApiRequest<'a, CobrancaComVencimento> {
+        let endpoint = format!("{}/cobv/{}", self.inner.base_endpoint, txid);
+        self.inner.request_with_headers(Method::PUT, &endpoint, payload)
+    }
+
+    pub fn consultar_cobranca_txid(&self, txid: &CobTxid) -> ApiRequest<'a, CobrancaComVencimento> {
+        let endpoint = format!("{}/cobv/{}", self.inner.base_endpoint, txid);
+        self.inner.request_with_query(Method::GET, &endpoint, None::<()>)
+    }
+
+    pub fn consultar_cobrancas(&self, filtro: CobvFiltro) -> ApiRequest<'a, CobrancasComVencimento> {
+        let endpoint = format!("{}/cobv", self.inner.base_endpoint);
+        self.inner.request_with_query(Method::GET, &endpoint, Some(filtro))
+    }
+}
@@ -0,0 +1,355 @@
+//! Deduplicação dos eventos recebidos pelo webhook.
+//!
+//! PSP's entregam o mesmo callback novamente quando não recebem uma resposta de sucesso, e podem reenviar um
+//! `PixInput` já entregue, por exemplo quando uma devolução é realizada. [`Deduplicado`] envolve um
+//! [`WebHookHandler`] e o invoca somente para eventos ainda não processados:
+//!
+//! - um Pix recebido, identificado pelo seu `endToEndId`, repassado a [`WebHookHandler::handle`] sem suas
+//!   devoluções;
+//! - cada mudança de status de uma devolução, identificada pelo seu `rtrId`, repassada a
+//!   [`WebHookHandler::handle_devolucao`].
+//!
+//! Cada evento é registrado em um [`DeduplicationStore`] antes do handler ser invocado, e removido caso ele falhe,
+//! de forma que a próxima entrega do PSP o processe novamente, sem repetir os eventos que já foram processados.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use pix_api_client::deduplication::{Deduplicado, InMemoryStore};
+//! use pix_api_client::webhook::{HandlerError, PixInput, WebHookCallbackResponse};
+//!
+//! # async fn doc_test(callback: WebHookCallbackResponse) -> Result<(), HandlerError> {
+//! let handler = Deduplicado::new(
+//!     |pix: PixInput| async move {
+//!         println!("Pix {} recebido", pix.end_to_end_id);
+//!         Ok::<_, HandlerError>(())
+//!     },
+//!     InMemoryStore::new(),
+//! );
+//!
+//! // entregas repetidas do mesmo callback não invocam o handler novamente
+//! handler.handle_callback(callback.clone()).await?;
+//! handler.handle_callback(callback).await?;
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::webhook::{Devolucoes, HandlerError, PixInput, WebHookCallbackResponse, WebHookHandler};
+
+/// Registro dos eventos já processados. Implemente-o sobre um banco de dados ou cache compartilhado quando houver
+/// mais de uma instância recebendo o webhook.
+#[async_trait]
+pub trait DeduplicationStore: Send + Sync + 'static {
+    /// Registra `key`, retornando `false` caso ela já estivesse registrada.
+    async fn insert(&self, key: &str) -> Result<bool, HandlerError>;
+
+    /// Remove `key`, para que o evento seja processado novamente na próxima entrega.
+    async fn remove(&self, key: &str) -> Result<(), HandlerError>;
+}
+
+/// Mantém os eventos em memória, enquanto o processo estiver em execução. Os eventos nunca são descartados.
+#[derive(Debug, Default)]
+pub struct InMemoryStore {
+    keys: Mutex<HashSet<String>>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> InMemoryStore {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl DeduplicationStore for InMemoryStore {
+    async fn insert(&self, key: &str) -> Result<bool, HandlerError> {
+        Ok(self.keys.lock().unwrap().insert(key.to_string()))
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), HandlerError> {
+        self.keys.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+fn devolucao_key(devolucao: &Devolucoes) -> String {
+    format!("devolucao:{}:{:?}", devolucao.rtr_id, devolucao.status)
+}
+
+/// Invoca `handler` uma única vez para cada evento, mesmo que o PSP o entregue diversas vezes. Os eventos de um
+/// `PixInput` são processados em ordem, e o primeiro erro interrompe os seguintes.
+#[derive(Debug)]
+pub struct Deduplicado<H, S> {
+    handler: H,
+    store: S,
+}
+
+impl<H, S> Deduplicado<H, S>
+where
+    H: WebHookHandler,
+    S: DeduplicationStore,
+{
+    pub fn new(handler: H, store: S) -> Deduplicado<H, S> {
+        Self { handler, store }
+    }
+
+    /// Processa cada `PixInput` do callback, interrompendo no primeiro erro.
+    pub async fn handle_callback(&self, callback: WebHookCallbackResponse) -> Result<(), HandlerError> {
+        for pix in callback.pix {
+            self.handle(pix).await?;
+        }
+        Ok(())
+    }
+
+    /// Registra `key` e invoca `handler` caso o evento ainda não tenha sido processado, removendo `key` quando ele
+    /// falha. Uma falha ao remover é somente registrada, preservando o erro original.
+    async fn once<F, Fut>(&self, key: String, handler: F) -> Result<(), HandlerError>
+    where
+        F: FnOnce() -> Fut + Send,
+        Fut: Future<Output = Result<(), HandlerError>> + Send,
+    {
+        if !self.store.insert(&key).await? {
+            tracing::debug!(%key, "duplicated webhook event skipped");
+            return Ok(());
+        }
+
+        let result = handler().await;
+        if result.is_err() {
+            if let Err(error) = self.store.remove(&key).await {
+                tracing::warn!(%key, %error, "failed to remove deduplication key, the event won't be processed again");
+            }
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl<H, S> WebHookHandler for Deduplicado<H, S>
+where
+    H: WebHookHandler,
+    S: DeduplicationStore,
+{
+    async fn handle(&self, mut pix: PixInput) -> Result<(), HandlerError> {
+        let end_to_end_id = pix.end_to_end_id.clone();
+        let devolucoes = pix.devolucoes.take().unwrap_or_default();
+
+        self.once(format!("pix:{}", end_to_end_id), || self.handler.handle(pix))
+            .await?;
+
+        for devolucao in devolucoes {
+            self.once(devolucao_key(&devolucao), || {
+                self.handler.handle_devolucao(&end_to_end_id, devolucao)
+            })
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn callback(devolucoes: &str) -> WebHookCallbackResponse {
+        serde_json::from_str(&format!(
+            r#"{{
+                "pix": [{{
+                    "endToEndId": "E12345678202009091221abcdef12345",
+                    "txid": "cd1fe328c875481285a6f233ae41b662",
+                    "chave": "7d9f0335-8dcc-4054-9bf9-0dbd61d36906",
+                    "valor": "100.00",
+                    "horario": "2020-09-10T13:03:33.902Z",
+                    "devolucoes": [{}]
+                }}]
+            }}"#,
+            devolucoes
+        ))
+        .unwrap()
+    }
+
+    fn devolucao(status: &str) -> String {
+        devolucao_com_rtr_id("D12345678202009091000abcde123456", status)
+    }
+
+    fn devolucao_com_rtr_id(rtr_id: &str, status: &str) -> String {
+        format!(
+            r#"{{
+                "id": "123456",
+                "rtrId": "{}",
+                "valor": "7.89",
+                "horario": {{ "solicitacao": "2020-09-11T15:25:59.411Z" }},
+                "status": "{}"
+            }}"#,
+            rtr_id, status
+        )
+    }
+
+    /// Falha ao registrar devoluções e, opcionalmente, ao remover qualquer chave.
+    #[derive(Default)]
+    struct FlakyStore {
+        inner: InMemoryStore,
+        fail_remove: bool,
+    }
+
+    #[async_trait]
+    impl DeduplicationStore for FlakyStore {
+        async fn insert(&self, key: &str) -> Result<bool, HandlerError> {
+            if key.starts_with("devolucao:") {
+                return Err("store unavailable".into());
+            }
+            self.inner.insert(key).await
+        }
+
+        async fn remove(&self, key: &str) -> Result<(), HandlerError> {
+            if self.fail_remove {
+                return Err("store unavailable".into());
+            }
+            self.inner.remove(key).await
+        }
+    }
+
+    /// Registra os eventos recebidos, falhando nas primeiras `falhas_devolucao` devoluções.
+    #[derive(Clone, Default)]
+    struct Registro {
+        pix: Arc<Mutex<Vec<PixInput>>>,
+        devolucoes: Arc<Mutex<Vec<String>>>,
+        falhas_devolucao: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl WebHookHandler for Registro {
+        async fn handle(&self, pix: PixInput) -> Result<(), HandlerError> {
+            self.pix.lock().unwrap().push(pix);
+            Ok(())
+        }
+
+        async fn handle_devolucao(&self, end_to_end_id: &str, devolucao: Devolucoes) -> Result<(), HandlerError> {
+            let falhas = self.falhas_devolucao.load(Ordering::SeqCst);
+            if falhas > 0 {
+                self.falhas_devolucao.store(falhas - 1, Ordering::SeqCst);
+                return Err("database unavailable".into());
+            }
+
+            let evento = format!("{}:{}:{:?}", end_to_end_id, devolucao.rtr_id, devolucao.status);
+            self.devolucoes.lock().unwrap().push(evento);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn t_eventos_processados_uma_vez() {
+        let registro = Registro::default();
+        let handler = Deduplicado::new(registro.clone(), InMemoryStore::new());
+
+        handler.handle_callback(callback("")).await.unwrap();
+        handler.handle_callback(callback("")).await.unwrap();
+        for _ in 0..2 {
+            handler
+                .handle_callback(callback(&devolucao("EM_PROCESSAMENTO")))
+                .await
+                .unwrap();
+        }
+        handler
+            .handle_callback(callback(&devolucao("DEVOLVIDO")))
+            .await
+            .unwrap();
+
+        let pix = registro.pix.lock().unwrap();
+        assert_eq!(pix.len(), 1);
+        assert_eq!(pix[0].devolucoes, None);
+        assert_eq!(
+            *registro.devolucoes.lock().unwrap(),
+            [
+                "E12345678202009091221abcdef12345:D12345678202009091000abcde123456:EM_PROCESSAMENTO",
+                "E12345678202009091221abcdef12345:D12345678202009091000abcde123456:DEVOLVIDO",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn t_falha_permite_reprocessar() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let handler = Deduplicado::new(
+            move |_: PixInput| {
+                let counter = counter.clone();
+                async move {
+                    match counter.fetch_add(1, Ordering::SeqCst) {
+                        0 => Err("database unavailable".into()),
+                        _ => Ok(()),
+                    }
+                }
+            },
+            InMemoryStore::new(),
+        );
+
+        assert!(handler.handle_callback(callback("")).await.is_err());
+        handler.handle_callback(callback("")).await.unwrap();
+        handler.handle_callback(callback("")).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn t_falha_de_devolucao_nao_repete_o_pix() {
+        let registro = Registro {
+            falhas_devolucao: Arc::new(AtomicUsize::new(1)),
+            ..Default::default()
+        };
+        let handler = Deduplicado::new(registro.clone(), InMemoryStore::new());
+
+        let primeira = devolucao_com_rtr_id("D12345678202009091000abcde000001", "DEVOLVIDO");
+        let segunda = devolucao_com_rtr_id("D12345678202009091000abcde000002", "EM_PROCESSAMENTO");
+        let ambas = callback(&format!("{},{}", primeira, segunda));
+
+        assert!(handler.handle_callback(ambas.clone()).await.is_err());
+        handler.handle_callback(ambas.clone()).await.unwrap();
+        handler.handle_callback(ambas).await.unwrap();
+
+        assert_eq!(registro.pix.lock().unwrap().len(), 1);
+        assert_eq!(
+            *registro.devolucoes.lock().unwrap(),
+            [
+                "E12345678202009091221abcdef12345:D12345678202009091000abcde000001:DEVOLVIDO",
+                "E12345678202009091221abcdef12345:D12345678202009091000abcde000002:EM_PROCESSAMENTO",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn t_falha_do_store_preserva_pix_processado() {
+        let registro = Registro::default();
+        let handler = Deduplicado::new(registro.clone(), FlakyStore::default());
+
+        for _ in 0..2 {
+            let error = handler
+                .handle_callback(callback(&devolucao("DEVOLVIDO")))
+                .await
+                .unwrap_err();
+            assert_eq!(error.to_string(), "store unavailable");
+        }
+        // o Pix, processado antes da falha ao registrar a devolução, não é repetido
+        assert_eq!(registro.pix.lock().unwrap().len(), 1);
+        assert!(registro.devolucoes.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn t_falha_ao_remover_preserva_erro_do_handler() {
+        let handler = Deduplicado::new(
+            |_: PixInput| async { Err::<(), HandlerError>("database unavailable".into()) },
+            FlakyStore {
+                fail_remove: true,
+                ..Default::default()
+            },
+        );
+
+        let error = handler.handle_callback(callback("")).await.unwrap_err();
+        assert_eq!(error.to_string(), "database unavailable");
+    }
+}
@@ -1,8 +1,12 @@
+use std::fmt;
+
+use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 pub type ApiResult<T> = Result<T, PixError>;
 
+/// Formato de erro legado, ainda utilizado por alguns PSP's no lugar da RFC 7807.
 #[derive(Debug, Serialize, Deserialize)]
 struct GenericErrorMessage {
     nome: String,
@@ -17,18 +21,128 @@ struct Erros {
     mensagem: String,
 }
 
+/// Detalhes de um problema, conforme a RFC 7807 adotada pela API Pix.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Problema {
+    #[serde(rename = "type")]
+    pub tipo: String,
+    #[serde(rename = "title")]
+    pub titulo: String,
+    pub status: u16,
+    #[serde(rename = "detail")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detalhe: Option<String>,
+    #[serde(rename = "correlationId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub violacoes: Option<Vec<Violacao>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Violacao {
+    pub razao: String,
+    pub propriedade: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valor: Option<String>,
+}
+
+impl GenericErrorMessage {
+    fn into_problema(self, status: StatusCode) -> Problema {
+        let violacoes = self.errors.map(|errors| {
+            errors
+                .into_iter()
+                .map(|erro| Violacao {
+                    razao: erro.mensagem,
+                    propriedade: erro.caminho,
+                    valor: None,
+                })
+                .collect()
+        });
+
+        Problema {
+            tipo: self.nome,
+            titulo: self.mensagem,
+            status: status.as_u16(),
+            detalhe: None,
+            correlation_id: None,
+            violacoes,
+        }
+    }
+}
+
+/// A non successful response, with its body parsed as a `Problema` whenever possible.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorResponse {
+    pub status: StatusCode,
+    pub problema: Option<Problema>,
+    /// Raw response body, kept for diagnostics.
+    pub body: String,
+}
+
+impl ErrorResponse {
+    pub(crate) fn new(status: StatusCode, body: String) -> ErrorResponse {
+        let problema = serde_json::from_str::<Problema>(&body).ok().or_else(|| {
+            serde_json::from_str::<GenericErrorMessage>(&body)
+                .ok()
+                .map(|message| message.into_problema(status))
+        });
+
+        Self { status, problema, body }
+    }
+
+    /// Violations reported by the PSP, usually present on validation errors.
+    pub fn violacoes(&self) -> &[Violacao] {
+        self.problema
+            .as_ref()
+            .and_then(|problema| problema.violacoes.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for ErrorResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.problema {
+            Some(Problema {
+                titulo,
+                detalhe: Some(detalhe),
+                ..
+            }) => write!(f, "{}: {} {}", self.status, titulo, detalhe),
+            Some(Problema { titulo, .. }) => write!(f, "{}: {}", self.status, titulo),
+            None => write!(f, "{}: {}", self.status, self.body),
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum PixError {
     /// Error 401
-    #[error("Invalid credentials")]
-    InvalidCredentials,
+    #[error("Invalid credentials. {0}")]
+    InvalidCredentials(Box<ErrorResponse>),
 
     /// Error 401
     #[error("Access token is expired. Renew it")]
     ExpiredToken(String),
 
-    #[error("There is something wrong with the payload this library sent.")]
-    PayloadError,
+    /// Error 400, with the violations found by the PSP.
+    #[error("There is something wrong with the payload this library sent. {0}")]
+    PayloadError(Box<ErrorResponse>),
+
+    /// Error 403, usually when the token lacks the scope required by the endpoint.
+    #[error("Forbidden. {0}")]
+    Forbidden(Box<ErrorResponse>),
+
+    /// Error 404
+    #[error("Not found. {0}")]
+    NotFound(Box<ErrorResponse>),
+
+    /// Error 503
+    #[error("Service unavailable. {0}")]
+    ServiceUnavailable(Box<ErrorResponse>),
+
+    /// Any other non successful status.
+    #[error("Unexpected response. {0}")]
+    UnexpectedStatus(Box<ErrorResponse>),
 
     #[error("`{0}`")]
     Other(String),
@@ -38,4 +152,107 @@ pub enum PixError {
 
     #[error(transparent)]
     NetworkError(#[from] reqwest::Error),
+
+    /// The client identity, or a root certificate, could not be parsed.
+    #[error("Invalid certificate: {0}")]
+    InvalidCertificate(#[source] reqwest::Error),
+}
+
+impl PixError {
+    pub(crate) fn from_response(status: StatusCode, body: String) -> PixError {
+        let response = Box::new(ErrorResponse::new(status, body));
+
+        match status {
+            StatusCode::UNAUTHORIZED => PixError::InvalidCredentials(response),
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => PixError::PayloadError(response),
+            StatusCode::FORBIDDEN => PixError::Forbidden(response),
+            StatusCode::NOT_FOUND => PixError::NotFound(response),
+            StatusCode::SERVICE_UNAVAILABLE => PixError::ServiceUnavailable(response),
+            _ => PixError::UnexpectedStatus(response),
+        }
+    }
+
+    /// The response that caused this error, when the PSP replied with a non successful status.
+    pub fn response(&self) -> Option<&ErrorResponse> {
+        match self {
+            PixError::InvalidCredentials(response)
+            | PixError::PayloadError(response)
+            | PixError::Forbidden(response)
+            | PixError::NotFound(response)
+            | PixError::ServiceUnavailable(response)
+            | PixError::UnexpectedStatus(response) => Some(&**response),
+            _ => None,
+        }
+    }
+
+    pub fn problema(&self) -> Option<&Problema> {
+        self.response().and_then(|response| response.problema.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_problema_com_violacoes() {
+        let body = r#"{
+            "type": "https://pix.bcb.gov.br/api/v2/error/CobOperacaoInvalida",
+            "title": "Cobrança inválida.",
+            "status": 400,
+            "detail": "A requisição que busca alterar ou criar uma cobrança para pagamento imediato não respeita o schema ou está semanticamente errada.",
+            "correlationId": "b1f2f4b8-0b8b-4f1f-9a55-9d4c3f6e1f7a",
+            "violacoes": [
+                {
+                    "razao": "O campo cob.valor.original não respeita o schema.",
+                    "propriedade": "cob.valor.original",
+                    "valor": "10,00"
+                }
+            ]
+        }"#;
+
+        let error = PixError::from_response(StatusCode::BAD_REQUEST, body.to_string());
+        let response = error.response().unwrap();
+
+        assert!(matches!(error, PixError::PayloadError(_)));
+        assert_eq!(response.body, body);
+        assert_eq!(response.violacoes()[0].propriedade, "cob.valor.original");
+        assert_eq!(
+            error.problema().unwrap().correlation_id.as_deref(),
+            Some("b1f2f4b8-0b8b-4f1f-9a55-9d4c3f6e1f7a")
+        );
+    }
+
+    #[test]
+    fn t_legacy_error_message() {
+        let body = r#"{
+            "nome": "valor_invalido",
+            "mensagem": "Requisição com parâmetros inválidos",
+            "erros": null,
+            "errors": [
+                {
+                    "chave": "tipo_invalido",
+                    "caminho": ".valor.original",
+                    "mensagem": "Propriedade com tipo inválido"
+                }
+            ]
+        }"#;
+
+        let error = PixError::from_response(StatusCode::BAD_REQUEST, body.to_string());
+        let problema = error.problema().unwrap();
+
+        assert_eq!(problema.titulo, "Requisição com parâmetros inválidos");
+        assert_eq!(problema.violacoes.as_ref().unwrap()[0].propriedade, ".valor.original");
+    }
+
+    #[test]
+    fn t_non_json_body() {
+        let error = PixError::from_response(StatusCode::SERVICE_UNAVAILABLE, "upstream timeout".to_string());
+
+        assert!(matches!(&error, PixError::ServiceUnavailable(response) if response.problema.is_none()));
+        assert_eq!(
+            error.to_string(),
+            "Service unavailable. 503 Service Unavailable: upstream timeout"
+        );
+    }
 }
@@ -1,26 +1,344 @@
 use std::borrow::Cow;
 
-use pix_brcode::qr_dinamico::PixDinamicoSchema;
+use pix_brcode::qr_dinamico::{PixDinamicoBuilder, PixDinamicoError, PixDinamicoSchema};
+use thiserror::Error;
 
-use crate::cob::CobrancaImediata;
+use crate::cob::{CobrancaImediata, Location};
+use crate::cobv::CobrancaComVencimento;
 
-pub trait FromResponse<'a, T> {
-    fn from_cobranca_imediata_basic<MN: Into<Cow<'a, str>>, MC: Into<Cow<'a, str>>>(
-        cob: CobrancaImediata,
+#[derive(Debug, Error, PartialEq)]
+pub enum BrCodeError {
+    #[error("The charge has no location, which is required by dynamic BR Codes")]
+    MissingLocation,
+    #[error("Invalid BR Code: {0}")]
+    Invalid(#[from] PixDinamicoError),
+    /// O `recebedor` não tem o campo informado, `nome` ou `cidade`. Uma cobrança sem `recebedor` não tem `nome`.
+    #[error("The charge's recebedor has no {0}")]
+    MissingRecebedor(&'static str),
+}
+
+/// Gera o BR Code dinâmico das cobranças retornadas pelo PSP.
+///
+/// O `txid` do BR Code dinâmico é sempre `***`, pois o app do pagador obtém a cobrança pela url da location.
+///
+/// Implementado por [`PixDinamicoSchema`], com nomes e cidades normalizados pelo
+/// [`Normalizer`](pix_brcode::normalizacao::Normalizer) padrão, e por [`PixDinamicoBuilder`], que permite configurar
+/// a normalização antes de [`PixDinamicoBuilder::build`].
+pub trait FromResponse<'a>: Sized {
+    /// O valor é omitido quando o pagador pode alterá-lo. A location da cobrança imediata é vinculada a um único
+    /// pagamento, indicado pelo `point_of_initiation_method` `12`.
+    fn try_from_cobranca_imediata<MN, MC>(
+        cob: &'a CobrancaImediata,
+        merchant_name: MN,
+        merchant_city: MC,
+    ) -> Result<Self, BrCodeError>
+    where
+        MN: Into<Cow<'a, str>>,
+        MC: Into<Cow<'a, str>>;
+
+    /// Ver [`FromResponse::try_from_cobranca_imediata`].
+    ///
+    /// # Panics
+    ///
+    /// Caso a cobrança não tenha location, ou o BR Code gerado seja inválido.
+    #[deprecated(note = "use `try_from_cobranca_imediata`, which reports a missing location or an invalid BR Code")]
+    fn from_cobranca_imediata_basic<MN, MC>(cob: CobrancaImediata, merchant_name: MN, merchant_city: MC) -> Self
+    where
+        MN: Into<Cow<'a, str>>,
+        MC: Into<Cow<'a, str>>;
+
+    /// Utiliza o nome fantasia, ou nome, e a cidade do `recebedor` informados pelo PSP. O valor é omitido, já que
+    /// multa, juros e descontos são calculados pelo PSP na data do pagamento.
+    fn try_from_cobranca_com_vencimento(cob: &'a CobrancaComVencimento) -> Result<Self, BrCodeError>;
+
+    /// BR Code de uma location reutilizável, criada por [`crate::loc::LocEndpoint`], que pode ser vinculada a
+    /// diversas cobranças ao longo do tempo, como um QR Code impresso. Não contém valor, e pode ser pago mais de
+    /// uma vez.
+    fn try_from_location<MN, MC>(
+        location: &'a Location,
         merchant_name: MN,
         merchant_city: MC,
-    ) -> T;
+    ) -> Result<Self, BrCodeError>
+    where
+        MN: Into<Cow<'a, str>>,
+        MC: Into<Cow<'a, str>>;
 }
 
-impl<'a> FromResponse<'a, PixDinamicoSchema<'a>> for PixDinamicoSchema<'a> {
-    fn from_cobranca_imediata_basic<MN: Into<Cow<'a, str>>, MC: Into<Cow<'a, str>>>(
-        cob: CobrancaImediata,
+impl<'a> FromResponse<'a> for PixDinamicoBuilder<'a> {
+    fn try_from_cobranca_imediata<MN, MC>(
+        cob: &'a CobrancaImediata,
         merchant_name: MN,
         merchant_city: MC,
-    ) -> PixDinamicoSchema<'a> {
-        let valor_original: Cow<_> = cob.valor.original.into();
-        let location = cob.location.unwrap();
+    ) -> Result<Self, BrCodeError>
+    where
+        MN: Into<Cow<'a, str>>,
+        MC: Into<Cow<'a, str>>,
+    {
+        let location = cob.location.as_ref().ok_or(BrCodeError::MissingLocation)?;
+        let amount = (cob.valor.permite_alteracao != Some(1)).then_some(cob.valor.original.as_str());
+
+        Ok(cobranca_imediata(
+            location.url.as_str(),
+            amount,
+            merchant_name,
+            merchant_city,
+        ))
+    }
+
+    fn from_cobranca_imediata_basic<MN, MC>(cob: CobrancaImediata, merchant_name: MN, merchant_city: MC) -> Self
+    where
+        MN: Into<Cow<'a, str>>,
+        MC: Into<Cow<'a, str>>,
+    {
+        let location = cob.location.expect("the charge has no location");
+        let amount = (cob.valor.permite_alteracao != Some(1)).then_some(cob.valor.original);
+
+        cobranca_imediata(location.url, amount, merchant_name, merchant_city)
+    }
+
+    fn try_from_cobranca_com_vencimento(cob: &'a CobrancaComVencimento) -> Result<Self, BrCodeError> {
+        let location = cob.location.as_ref().ok_or(BrCodeError::MissingLocation)?;
+        let recebedor = cob.recebedor.as_ref().ok_or(BrCodeError::MissingRecebedor("nome"))?;
+
+        let merchant_name = recebedor
+            .nome_fantasia
+            .as_deref()
+            .or(recebedor.nome.as_deref())
+            .ok_or(BrCodeError::MissingRecebedor("nome"))?;
+        let merchant_city = recebedor
+            .cidade
+            .as_deref()
+            .ok_or(BrCodeError::MissingRecebedor("cidade"))?;
+
+        let mut builder = builder(location).merchant(merchant_name, merchant_city);
+        if let Some(cep) = &recebedor.cep {
+            builder = builder.postal_code(cep.replace('-', ""));
+        }
+        Ok(builder)
+    }
+
+    fn try_from_location<MN, MC>(
+        location: &'a Location,
+        merchant_name: MN,
+        merchant_city: MC,
+    ) -> Result<Self, BrCodeError>
+    where
+        MN: Into<Cow<'a, str>>,
+        MC: Into<Cow<'a, str>>,
+    {
+        Ok(builder(location).merchant(merchant_name, merchant_city).reusable(true))
+    }
+}
+
+impl<'a> FromResponse<'a> for PixDinamicoSchema<'a> {
+    fn try_from_cobranca_imediata<MN, MC>(
+        cob: &'a CobrancaImediata,
+        merchant_name: MN,
+        merchant_city: MC,
+    ) -> Result<Self, BrCodeError>
+    where
+        MN: Into<Cow<'a, str>>,
+        MC: Into<Cow<'a, str>>,
+    {
+        let builder = PixDinamicoBuilder::try_from_cobranca_imediata(cob, merchant_name, merchant_city)?;
+        Ok(builder.build()?)
+    }
+
+    #[allow(deprecated)]
+    fn from_cobranca_imediata_basic<MN, MC>(cob: CobrancaImediata, merchant_name: MN, merchant_city: MC) -> Self
+    where
+        MN: Into<Cow<'a, str>>,
+        MC: Into<Cow<'a, str>>,
+    {
+        PixDinamicoBuilder::from_cobranca_imediata_basic(cob, merchant_name, merchant_city)
+            .build()
+            .expect("invalid BR Code")
+    }
+
+    fn try_from_cobranca_com_vencimento(cob: &'a CobrancaComVencimento) -> Result<Self, BrCodeError> {
+        Ok(PixDinamicoBuilder::try_from_cobranca_com_vencimento(cob)?.build()?)
+    }
+
+    fn try_from_location<MN, MC>(
+        location: &'a Location,
+        merchant_name: MN,
+        merchant_city: MC,
+    ) -> Result<Self, BrCodeError>
+    where
+        MN: Into<Cow<'a, str>>,
+        MC: Into<Cow<'a, str>>,
+    {
+        let builder = PixDinamicoBuilder::try_from_location(location, merchant_name, merchant_city)?;
+        Ok(builder.build()?)
+    }
+}
+
+fn builder(location: &Location) -> PixDinamicoBuilder<'_> {
+    PixDinamicoSchema::builder().location(location.url.trim_start_matches("https://"))
+}
+
+/// Comum às conversões da cobrança imediata emprestada e da recebida por valor.
+fn cobranca_imediata<'a, L, A, MN, MC>(
+    location: L,
+    amount: Option<A>,
+    merchant_name: MN,
+    merchant_city: MC,
+) -> PixDinamicoBuilder<'a>
+where
+    L: Into<Cow<'a, str>>,
+    A: Into<Cow<'a, str>>,
+    MN: Into<Cow<'a, str>>,
+    MC: Into<Cow<'a, str>>,
+{
+    let location = match location.into() {
+        Cow::Borrowed(location) => Cow::Borrowed(location.trim_start_matches("https://")),
+        Cow::Owned(location) => Cow::Owned(location.trim_start_matches("https://").to_string()),
+    };
+    let builder = PixDinamicoSchema::builder()
+        .location(location)
+        .merchant(merchant_name, merchant_city);
+
+    match amount {
+        Some(amount) => builder.amount(amount),
+        None => builder,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pix_brcode::normalizacao::Normalizer;
+    use pix_brcode::qr_dinamico::MAX_LOCATION;
+
+    use super::*;
+    use crate::cobv::Recebedor;
+
+    fn cobranca_imediata() -> CobrancaImediata {
+        serde_json::from_str(
+            r#"{
+                "calendario": { "criacao": "2020-09-09T20:15:00.358Z", "expiracao": 3600 },
+                "txid": "7978c0c97ea847e78e8849634473c1f1",
+                "revisao": 0,
+                "loc": {
+                    "id": 789,
+                    "location": "pix.example.com/qr/9d36b84fc70b478fb95c12729b90ca25",
+                    "tipoCob": "cob",
+                    "criacao": "2020-09-09T20:15:00.358Z"
+                },
+                "status": "ATIVA",
+                "devedor": { "cpf": "12345678909", "nome": "Francisco da Silva" },
+                "valor": { "original": "567.89" },
+                "chave": "a1f4102e-a446-4a57-bcce-6fa48899c1d1"
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn t_cobranca_imediata() {
+        let cob = cobranca_imediata();
+        let schema =
+            PixDinamicoSchema::try_from_cobranca_imediata(&cob, "Padaria São João da Boa Vista", "São José dos Campos")
+                .unwrap();
+
+        assert_eq!(schema.point_of_initiation_method.as_deref(), Some("12"));
+        assert_eq!(schema.transaction_amount.as_deref(), Some("567.89"));
+        assert_eq!(schema.merchant_name, "Padaria Sao Joao da Boa");
+        assert_eq!(schema.merchant_city, "Sao Jose dos");
+        assert!(schema.serialize_with_src().starts_with("000201010212"));
+        assert_eq!(
+            schema.merchant_account_information.merchant_location_url,
+            "pix.example.com/qr/9d36b84fc70b478fb95c12729b90ca25"
+        );
+
+        let mut sem_location = cob.clone();
+        sem_location.location = None;
+        assert_eq!(
+            PixDinamicoSchema::try_from_cobranca_imediata(&sem_location, "Loja", "Cidade").unwrap_err(),
+            BrCodeError::MissingLocation
+        );
+
+        let mut longa = cob.clone();
+        longa.location.as_mut().unwrap().url = format!("pix.example.com/{}", "a".repeat(MAX_LOCATION));
+        assert!(matches!(
+            PixDinamicoSchema::try_from_cobranca_imediata(&longa, "Loja", "Cidade"),
+            Err(BrCodeError::Invalid(PixDinamicoError::InvalidLocation(_)))
+        ));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn t_from_cobranca_imediata_basic() {
+        let cob = cobranca_imediata();
+        let esperado = PixDinamicoSchema::try_from_cobranca_imediata(&cob, "Loja", "Cidade").unwrap();
+
+        let schema = PixDinamicoSchema::from_cobranca_imediata_basic(cob.clone(), "Loja", "Cidade");
+        assert_eq!(schema.serialize_with_src(), esperado.serialize_with_src());
+    }
+
+    #[test]
+    fn t_normalizacao_configuravel() {
+        let cob = cobranca_imediata();
+
+        let original = PixDinamicoBuilder::try_from_cobranca_imediata(&cob, "Padaria  Sao Joao", "Brasilia")
+            .unwrap()
+            .normalize(false)
+            .build()
+            .unwrap();
+        assert_eq!(original.merchant_name, "Padaria  Sao Joao");
+        assert_eq!(original.merchant_city, "Brasilia");
+
+        let acentuado = PixDinamicoBuilder::try_from_cobranca_imediata(&cob, "Padaria São João", "Brasília")
+            .unwrap()
+            .normalize(false)
+            .build();
+        assert!(matches!(acentuado, Err(PixDinamicoError::InvalidMerchantName(_))));
+
+        let maiusculas = PixDinamicoBuilder::try_from_cobranca_imediata(&cob, "Padaria São João", "Brasília")
+            .unwrap()
+            .normalizer(Normalizer::new().uppercase(true))
+            .build()
+            .unwrap();
+        assert_eq!(maiusculas.merchant_name, "PADARIA SAO JOAO");
+        assert_eq!(maiusculas.merchant_city, "BRASILIA");
+        assert_eq!(maiusculas.transaction_amount.as_deref(), Some("567.89"));
+    }
+
+    #[test]
+    fn t_cobranca_com_vencimento() {
+        let imediata = cobranca_imediata();
+        let mut cob = CobrancaComVencimento {
+            location: imediata.location.clone(),
+            ..Default::default()
+        };
+        assert_eq!(
+            PixDinamicoSchema::try_from_cobranca_com_vencimento(&cob).unwrap_err(),
+            BrCodeError::MissingRecebedor("nome")
+        );
+
+        cob.recebedor = Some(Recebedor {
+            nome: Some("Empresa de Serviços SA".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(
+            PixDinamicoSchema::try_from_cobranca_com_vencimento(&cob).unwrap_err(),
+            BrCodeError::MissingRecebedor("cidade")
+        );
+
+        cob.recebedor = Some(Recebedor {
+            nome: Some("Empresa de Serviços SA".to_string()),
+            nome_fantasia: Some("Serviços".to_string()),
+            cidade: Some("Uberaba".to_string()),
+            cep: Some("38025140".to_string()),
+            ..Default::default()
+        });
+        let schema = PixDinamicoSchema::try_from_cobranca_com_vencimento(&cob).unwrap();
+
+        assert_eq!(schema.merchant_name, "Servicos");
+        assert_eq!(schema.merchant_city, "Uberaba");
+        assert_eq!(schema.postal_code.as_deref(), Some("38025140"));
+        assert_eq!(schema.transaction_amount, None);
 
-        PixDinamicoSchema::standard(merchant_name, merchant_city, valor_original, location.url)
+        let reutilizavel = PixDinamicoSchema::try_from_location(imediata.location.as_ref().unwrap(), "Loja", "Cidade");
+        assert_eq!(reutilizavel.unwrap().point_of_initiation_method, None);
     }
 }
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use crate::cob::{Location, TipoCob};
+use crate::paginacao::{PaginacaoParams, Paginado, Parametros};
+use crate::{ApiRequest, PixClient};
+
+pub struct LocEndpoint<'a> {
+    inner: &'a PixClient,
+}
+
+impl PixClient {
+    pub fn loc(&self) -> LocEndpoint<'_> {
+        LocEndpoint { inner: self }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LocPayload {
+    #[serde(rename = "tipoCob")]
+    tipo_cob: TipoCob,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Locations {
+    pub parametros: Parametros,
+    pub loc: Vec<Location>,
+}
+
+impl Paginado for Locations {
+    type Item = Location;
+
+    fn parametros(&self) -> &Parametros {
+        &self.parametros
+    }
+
+    fn into_items(self) -> Vec<Location> {
+        self.loc
+    }
+}
+
+/// Filtros aceitos pela listagem de locations.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LocFiltro {
+    pub inicio: DateTime<Utc>,
+    pub fim: DateTime<Utc>,
+    #[serde(rename = "txIdPresente")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txid_presente: Option<bool>,
+    #[serde(rename = "tipoCob")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tipo_cob: Option<TipoCob>,
+    #[serde(flatten)]
+    pub paginacao: PaginacaoParams,
+}
+
+impl LocFiltro {
+    pub fn new(inicio: DateTime<Utc>, fim: DateTime<Utc>) -> Self {
+        Self {
+            inicio,
+            fim,
+            txid_presente: None,
+            tipo_cob: None,
+            paginacao: Default::default(),
+        }
+    }
+
+    pub fn txid_presente(mut self, txid_presente: bool) -> Self {
+        self.txid_presente = Some(txid_presente);
+        self
+    }
+
+    pub fn tipo_cob(mut self, tipo_cob: TipoCob) -> Self {
+        self.tipo_cob = Some(tipo_cob);
+        self
+    }
+
+    pub fn paginacao(mut self, paginacao: PaginacaoParams) -> Self {
+        self.paginacao = paginacao;
+        self
+    }
+}
+
+impl<'a> LocEndpoint<'a> {
+    /// Cria uma nova location, que poderá ser vinculada posteriormente a uma cobrança do mesmo `tipo_cob`.
+    pub fn criar(&self, tipo_cob: TipoCob) -> ApiRequest<'a, Location> {
+        let endpoint = format!("{}/loc", self.inner.base_endpoint);
+        self.inner
+            .request_with_headers(Method::POST, &endpoint, LocPayload { tipo_cob })
+    }
+
+    pub fn consultar(&self, id: i64) -> ApiRequest<'a, Location> {
+        let endpoint = format!("{}/loc/{}", self.inner.base_endpoint, id);
+        self.inner.request_with_query(Method::GET, &endpoint, None::<()>)
+    }
+
+    pub fn consultar_locations(&self, filtro: LocFiltro) -> ApiRequest<'a, Locations> {
+        let endpoint = format!("{}/loc", self.inner.base_endpoint);
+        self.inner.request_with_query(Method::GET, &endpoint, Some(filtro))
+    }
+
+    /// Desvincula a cobrança da location, que passa a estar disponível para uma nova cobrança.
+    pub fn desvincular_txid(&self, id: i64) -> ApiRequest<'a, Location> {
+        let endpoint = format!("{}/loc/{}/txid", self.inner.base_endpoint, id);
+        self.inner.request_with_query(Method::DELETE, &endpoint, None::<()>)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_location_desvinculada() {
+        let response = r#"{
+            "id": 7768,
+            "location": "pix.example.com/qr/9d36b84f-c70b-478f-b95c-12729b90ca25",
+            "tipoCob": "cob",
+            "criacao": "2020-03-11T21:19:51.013Z"
+        }"#;
+
+        let location: Location = serde_json::from_str(response).unwrap();
+
+        assert_eq!(location.tipo_cob, TipoCob::Cob);
+        assert_eq!(location.txid, None);
+    }
+}
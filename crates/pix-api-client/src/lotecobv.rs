@@ -0,0 +1,196 @@
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use crate::cobv::CobrancaComVencimento;
+use crate::errors::Problema;
+use crate::paginacao::{PaginacaoParams, Paginado, Parametros};
+use crate::{ApiRequest, PixClient};
+
+pub struct LoteCobvEndpoint<'a> {
+    inner: &'a PixClient,
+}
+
+impl PixClient {
+    pub fn lotecobv(&self) -> LoteCobvEndpoint<'_> {
+        LoteCobvEndpoint { inner: self }
+    }
+}
+
+/// Status de cada cobrança dentro de um lote.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[allow(non_camel_case_types)]
+pub enum StatusCobrancaLote {
+    EM_PROCESSAMENTO,
+    CRIADA,
+    NEGADA,
+}
+
+/// Payload utilizado para criar um novo lote de cobranças com vencimento.
+/// Cada cobrança deve conter seu próprio `txid`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoteCobvPayload {
+    pub descricao: String,
+    pub cobsv: Vec<CobrancaComVencimento>,
+}
+
+impl LoteCobvPayload {
+    pub fn new(descricao: String, cobsv: Vec<CobrancaComVencimento>) -> Self {
+        Self { descricao, cobsv }
+    }
+}
+
+/// Payload utilizado para revisar cobranças de um lote já existente.
+/// Somente as cobranças presentes serão revisadas.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevisaoLoteCobvPayload {
+    pub cobsv: Vec<CobrancaComVencimento>,
+}
+
+impl RevisaoLoteCobvPayload {
+    pub fn new(cobsv: Vec<CobrancaComVencimento>) -> Self {
+        Self { cobsv }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoteCobv {
+    /// Id do lote. Presente somente nas listagens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub descricao: String,
+    pub criacao: String,
+    pub cobsv: Vec<CobrancaLote>,
+}
+
+impl LoteCobv {
+    /// Cobranças que foram negadas pelo PSP, junto de seus problemas.
+    pub fn negadas(&self) -> impl Iterator<Item = &CobrancaLote> {
+        self.cobsv.iter().filter(|cob| cob.status == StatusCobrancaLote::NEGADA)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CobrancaLote {
+    pub txid: String,
+    pub status: StatusCobrancaLote,
+
+    /// Presente após o processamento da cobrança.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub criacao: Option<String>,
+
+    /// Presente somente quando o `status` é `NEGADA`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problema: Option<Problema>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LotesCobv {
+    pub parametros: Parametros,
+    pub lotes: Vec<LoteCobv>,
+}
+
+impl Paginado for LotesCobv {
+    type Item = LoteCobv;
+
+    fn parametros(&self) -> &Parametros {
+        &self.parametros
+    }
+
+    fn into_items(self) -> Vec<LoteCobv> {
+        self.lotes
+    }
+}
+
+/// Filtros aceitos pela listagem de lotes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LoteCobvFiltro {
+    pub inicio: DateTime<Utc>,
+    pub fim: DateTime<Utc>,
+    #[serde(flatten)]
+    pub paginacao: PaginacaoParams,
+}
+
+impl LoteCobvFiltro {
+    pub fn new(inicio: DateTime<Utc>, fim: DateTime<Utc>) -> Self {
+        Self {
+            inicio,
+            fim,
+            paginacao: Default::default(),
+        }
+    }
+
+    pub fn paginacao(mut self, paginacao: PaginacaoParams) -> Self {
+        self.paginacao = paginacao;
+        self
+    }
+}
+
+impl<'a> LoteCobvEndpoint<'a> {
+    /// Cria um novo lote de cobranças com vencimento. O PSP responde sem corpo, e processa o lote de forma
+    /// assíncrona. Consulte o lote para saber o status de cada cobrança.
+    pub fn criar_lote(&self, id: i64, payload: LoteCobvPayload) -> ApiRequest<'a, ()> {
+        let endpoint = format!("{}/lotecobv/{}", self.inner.base_endpoint, id);
+        self.inner.request_with_headers(Method::PUT, &endpoint, payload)
+    }
+
+    pub fn revisar_lote(&self, id: i64, payload: RevisaoLoteCobvPayload) -> ApiRequest<'a, ()> {
+        let endpoint = format!("{}/lotecobv/{}", self.inner.base_endpoint, id);
+        self.inner.request_with_headers(Method::PATCH, &endpoint, payload)
+    }
+
+    pub fn consultar_lote(&self, id: i64) -> ApiRequest<'a, LoteCobv> {
+        let endpoint = format!("{}/lotecobv/{}", self.inner.base_endpoint, id);
+        self.inner.request_with_query(Method::GET, &endpoint, None::<()>)
+    }
+
+    pub fn consultar_lotes(&self, filtro: LoteCobvFiltro) -> ApiRequest<'a, LotesCobv> {
+        let endpoint = format!("{}/lotecobv", self.inner.base_endpoint);
+        self.inner.request_with_query(Method::GET, &endpoint, Some(filtro))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_lote_negado() {
+        let response = r#"{
+            "descricao": "Cobranças dos alunos de turma Alfa",
+            "criacao": "2020-11-01T20:15:00.358Z",
+            "cobsv": [
+                {
+                    "criacao": "2020-11-01T20:15:00.358Z",
+                    "txid": "fb2761260e554ad593c7226beb5cb650",
+                    "status": "CRIADA"
+                },
+                {
+                    "txid": "7978c0c97ea847e78e8849634473c1f1",
+                    "status": "NEGADA",
+                    "problema": {
+                        "type": "https://pix.bcb.gov.br/api/v2/error/CobVOperacaoInvalida",
+                        "title": "Cobrança inválida.",
+                        "status": 400,
+                        "detail": "A requisição que busca alterar ou criar uma cobrança com vencimento não respeita o schema ou está semanticamente errada.",
+                        "violacoes": [
+                            {
+                                "razao": "O objeto cobv.devedor não respeita o schema.",
+                                "propriedade": "cobv.devedor"
+                            }
+                        ]
+                    }
+                }
+            ]
+        }"#;
+
+        let lote: LoteCobv = serde_json::from_str(response).unwrap();
+        let negadas: Vec<_> = lote.negadas().collect();
+
+        assert_eq!(negadas.len(), 1);
+        assert_eq!(negadas[0].txid, "7978c0c97ea847e78e8849634473c1f1");
+
+        let violacoes = negadas[0].problema.as_ref().unwrap().violacoes.as_ref().unwrap();
+        assert_eq!(violacoes[0].propriedade, "cobv.devedor");
+    }
+}
@@ -0,0 +1,974 @@
+//! An in-process PSP, serving the Pix API over plain HTTP on localhost, for integration tests without a sandbox.
+//!
+//! It implements `/oauth/token`, `/cob`, `/cob/{txid}`, `/pix`, `/pix/{e2eid}` and `/webhook`, keeping every charge,
+//! Pix and webhook in memory. Failures can be scripted per method and path, and a payment of a charge can be
+//! simulated, firing the webhook registered for its key.
+//!
+//! The listings honor the period, filters and pagination of the Pix API, except the payer's `cpf` and `cnpj` of
+//! `/pix`, which aren't recorded. Query params that aren't supported are answered with `400`.
+//!
+//! Tokens are issued only for [`CLIENT_ID`] and [`CLIENT_SECRET`], and every other route accepts only tokens issued by
+//! this PSP that haven't expired yet.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use pix_api_client::cob::{CobrancaImediata, Devedor};
+//! use pix_api_client::mock_psp::{Method, MockPsp, StatusCode};
+//! use pix_api_client::Executor;
+//!
+//! # async fn doc_test() -> Result<(), anyhow::Error> {
+//! let psp = MockPsp::start().await?;
+//! let pix_client = psp.client();
+//!
+//! let devedor = Devedor::new_pessoa_fisica("12345678909".to_string(), "Francisco da Silva".to_string());
+//! let payload = CobrancaImediata::new(10.25, "my-key".to_string(), devedor);
+//! let cobranca = pix_client.cob().criar_cobranca_imediata(payload).execute().await?;
+//!
+//! // the next listing of charges fails once with 503
+//! psp.fail(Method::GET, "/cob", StatusCode::SERVICE_UNAVAILABLE, 1);
+//!
+//! let pix = psp.simulate_payment(&cobranca.txid.unwrap()).await?;
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::io;
+use std::net::{SocketAddr, TcpListener};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::body::{Body, Bytes};
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, Request};
+pub use axum::http::{Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::header::AUTHORIZATION;
+use serde_json::{json, Value};
+use tokio::task::JoinHandle;
+
+use crate::cob::{CobrancaImediata, CobrancaStatus, CobrancasImediatas, Location, TipoCob};
+use crate::errors::{ApiResult, PixError, Problema};
+use crate::oauth::TokenManager;
+use crate::paginacao::{Paginacao, Parametros};
+use crate::pix::PixRecebidos;
+use crate::webhook::{PixInput, WebHookCallbackResponse, WebHookResponse, WebHooks};
+use crate::{CobTxid, PixClient};
+
+pub const CLIENT_ID: &str = "mock-client-id";
+pub const CLIENT_SECRET: &str = "mock-client-secret";
+
+/// Lifetime of the issued tokens, in seconds.
+const TOKEN_EXPIRES_IN: u64 = 3600;
+
+#[derive(Debug, Default)]
+struct PspState {
+    cobs: BTreeMap<String, CobrancaImediata>,
+    /// Previous revisions of each charge, indexed by `revisao`.
+    revisoes: HashMap<String, Vec<CobrancaImediata>>,
+    pix: Vec<PixInput>,
+    webhooks: BTreeMap<String, WebHookResponse>,
+    failures: Vec<ScriptedFailure>,
+    /// Expiration of each issued token.
+    tokens: HashMap<String, Instant>,
+    next_location_id: i64,
+}
+
+#[derive(Debug)]
+struct ScriptedFailure {
+    method: Method,
+    path: String,
+    status: StatusCode,
+    remaining: usize,
+}
+
+type SharedState = Arc<Mutex<PspState>>;
+
+/// Handle of a running mock PSP. The server is stopped when it is dropped.
+#[derive(Debug)]
+pub struct MockPsp {
+    address: SocketAddr,
+    state: SharedState,
+    server: JoinHandle<()>,
+}
+
+impl MockPsp {
+    /// Starts the server on a random port of `127.0.0.1`.
+    pub async fn start() -> io::Result<MockPsp> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let state = SharedState::default();
+
+        let server = axum::Server::from_tcp(listener)
+            .map_err(io::Error::other)?
+            .serve(router(state.clone()).into_make_service());
+
+        let server = tokio::spawn(async move {
+            if let Err(error) = server.await {
+                tracing::error!(%error, "mock PSP stopped");
+            }
+        });
+
+        Ok(Self { address, state, server })
+    }
+
+    pub fn uri(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// A client pointing to this PSP, authenticated with [`CLIENT_ID`] and [`CLIENT_SECRET`].
+    pub fn client(&self) -> PixClient {
+        PixClient::builder(&self.uri())
+            .https_only(false)
+            .token_manager(TokenManager::basic(CLIENT_ID, CLIENT_SECRET))
+            .build()
+            .expect("a client without TLS options is always valid")
+    }
+
+    /// The next `times` requests with `method` to `path`, or to a path below it, are answered with `status`. A failure
+    /// for `/cob` thus also applies to `/cob/{txid}`, but not to `/cobv`.
+    ///
+    /// A scripted `401` rejects the request even with a valid token, or the credentials when `path` is `/oauth/token`.
+    pub fn fail(&self, method: Method, path: &str, status: StatusCode, times: usize) {
+        self.state.lock().unwrap().failures.push(ScriptedFailure {
+            method,
+            path: path.to_string(),
+            status,
+            remaining: times,
+        });
+    }
+
+    /// Every token issued so far expires now, so the next request with one of them is answered with `401`.
+    pub fn expire_tokens(&self) {
+        let now = Instant::now();
+
+        for expires_at in self.state.lock().unwrap().tokens.values_mut() {
+            *expires_at = now;
+        }
+    }
+
+    /// How many oauth tokens were issued so far.
+    pub fn issued_tokens(&self) -> usize {
+        self.state.lock().unwrap().tokens.len()
+    }
+
+    pub fn cobrancas(&self) -> Vec<CobrancaImediata> {
+        self.state.lock().unwrap().cobs.values().cloned().collect()
+    }
+
+    /// Pays the whole value of the active charge `txid`, concluding it, and posts the Pix to `{webhookUrl}/pix` when there
+    /// is a webhook registered for the charge's key.
+    pub async fn simulate_payment(&self, txid: &str) -> ApiResult<PixInput> {
+        let (pix, webhook_url) = {
+            let mut state = self.state.lock().unwrap();
+
+            let cob = state
+                .cobs
+                .get_mut(txid)
+                .ok_or_else(|| PixError::Other(format!("There is no charge with txid {}", txid)))?;
+            if cob.status != Some(CobrancaStatus::ATIVA) {
+                return Err(PixError::Other(format!("The charge {} is not active", txid)));
+            }
+            cob.status = Some(CobrancaStatus::CONCLUIDA);
+
+            let pix = PixInput {
+                end_to_end_id: format!("E00000000{}{}", Utc::now().format("%Y%m%d%H%M"), random_id(11)),
+                txid: Some(txid.to_string()),
+                chave: cob.chave_pix_recebedor.clone(),
+                valor: cob.valor.original.clone(),
+                horario: now(),
+                info_pagador: None,
+                devolucoes: None,
+                tipo: None,
+                status: None,
+            };
+            let webhook_url = state
+                .webhooks
+                .get(&pix.chave)
+                .map(|webhook| webhook.webhook_url.clone());
+
+            state.pix.push(pix.clone());
+            (pix, webhook_url)
+        };
+
+        if let Some(webhook_url) = webhook_url {
+            let callback = WebHookCallbackResponse { pix: vec![pix.clone()] };
+
+            reqwest::Client::new()
+                .post(format!("{}/pix", webhook_url.trim_end_matches('/')))
+                .json(&callback)
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+
+        Ok(pix)
+    }
+}
+
+impl Drop for MockPsp {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+fn router(state: SharedState) -> Router {
+    Router::new()
+        .route("/oauth/token", post(token))
+        .route("/cob", post(criar_cob).get(listar_cobs))
+        .route("/cob/:txid", put(criar_cob_txid).get(consultar_cob).patch(revisar_cob))
+        .route("/pix", get(listar_pix))
+        .route("/pix/:e2eid", get(consultar_pix))
+        .route("/webhook", get(listar_webhooks))
+        .route(
+            "/webhook/:chave",
+            put(criar_webhook).get(consultar_webhook).delete(cancelar_webhook),
+        )
+        .layer(middleware::from_fn_with_state(state.clone(), authorize))
+        .with_state(state)
+}
+
+/// Answers scripted failures, and rejects requests without a valid token issued by `/oauth/token`.
+async fn authorize(State(state): State<SharedState>, request: Request<Body>, next: Next<Body>) -> Response {
+    let scripted = {
+        let mut state = state.lock().unwrap();
+        let failure = state.failures.iter_mut().find(|failure| {
+            failure.remaining > 0 && failure.method == request.method() && within(request.uri().path(), &failure.path)
+        });
+
+        failure.map(|failure| {
+            failure.remaining -= 1;
+            failure.status
+        })
+    };
+    if let Some(status) = scripted {
+        return problema(status, "FalhaSimulada", "Falha programada no PSP simulado");
+    }
+
+    if request.uri().path() == "/oauth/token" {
+        return next.run(request).await;
+    }
+
+    let token = match authorization(request.headers(), "Bearer ") {
+        Some(token) => token,
+        None => return problema(StatusCode::UNAUTHORIZED, "AcessoNegado", "Token de acesso ausente"),
+    };
+    let valid = match state.lock().unwrap().tokens.get(token) {
+        Some(expires_at) => Instant::now() < *expires_at,
+        None => false,
+    };
+    if !valid {
+        return problema(
+            StatusCode::UNAUTHORIZED,
+            "AcessoNegado",
+            "Token de acesso inválido ou expirado",
+        );
+    }
+
+    next.run(request).await
+}
+
+/// Whether `path` is `prefix` or one of its sub paths.
+fn within(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+
+    match path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// Issues a token for [`CLIENT_ID`] and [`CLIENT_SECRET`], sent as Basic credentials, with the `client_credentials`
+/// grant type sent either as JSON or as a form.
+async fn token(State(state): State<SharedState>, headers: HeaderMap, body: Bytes) -> Response {
+    let credentials = base64::encode(format!("{}:{}", CLIENT_ID, CLIENT_SECRET));
+    if authorization(&headers, "Basic ") != Some(credentials.as_str()) {
+        return problema(StatusCode::UNAUTHORIZED, "AcessoNegado", "Credenciais inválidas");
+    }
+
+    let grant_type = match serde_json::from_slice::<Value>(&body) {
+        Ok(payload) => payload.get("grant_type").and_then(Value::as_str).map(str::to_string),
+        Err(_) => std::str::from_utf8(&body)
+            .unwrap_or_default()
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("grant_type="))
+            .map(str::to_string),
+    };
+    if grant_type.as_deref() != Some("client_credentials") {
+        return problema(
+            StatusCode::BAD_REQUEST,
+            "RequisicaoInvalida",
+            "grant_type deve ser client_credentials",
+        );
+    }
+
+    let mut state = state.lock().unwrap();
+    let access_token = format!("mock-token-{}", state.tokens.len() + 1);
+    state.tokens.insert(
+        access_token.clone(),
+        Instant::now() + Duration::from_secs(TOKEN_EXPIRES_IN),
+    );
+
+    Json(json!({
+        "access_token": access_token,
+        "token_type": "Bearer",
+        "expires_in": TOKEN_EXPIRES_IN,
+        "scope": "cob.write cob.read pix.read webhook.write webhook.read"
+    }))
+    .into_response()
+}
+
+/// The value of the `Authorization` header after `scheme`.
+fn authorization<'h>(headers: &'h HeaderMap, scheme: &str) -> Option<&'h str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix(scheme))
+}
+
+async fn criar_cob(State(state): State<SharedState>, Json(cob): Json<CobrancaImediata>) -> Response {
+    registrar_cob(&state, CobTxid::random().into(), cob)
+}
+
+async fn criar_cob_txid(
+    State(state): State<SharedState>,
+    Path(txid): Path<String>,
+    Json(cob): Json<CobrancaImediata>,
+) -> Response {
+    if let Err(e) = CobTxid::new(txid.as_str()) {
+        return problema(StatusCode::BAD_REQUEST, "CobOperacaoInvalida", &e.to_string());
+    }
+    if state.lock().unwrap().cobs.contains_key(&txid) {
+        return problema(StatusCode::BAD_REQUEST, "CobOperacaoInvalida", "O txid já está em uso");
+    }
+    registrar_cob(&state, txid, cob)
+}
+
+fn registrar_cob(state: &SharedState, txid: String, mut cob: CobrancaImediata) -> Response {
+    let mut state = state.lock().unwrap();
+    state.next_location_id += 1;
+
+    cob.txid = Some(txid.clone());
+    cob.revisao = Some(0);
+    cob.status = Some(CobrancaStatus::ATIVA);
+    cob.calendario.criacao = Some(now());
+    cob.location = Some(Location {
+        id: state.next_location_id,
+        url: format!("pix.example.com/qr/v2/{}", txid),
+        tipo_cob: TipoCob::Cob,
+        criacao: now(),
+        txid: Some(txid.clone()),
+    });
+
+    state.cobs.insert(txid, cob.clone());
+    (StatusCode::CREATED, Json(cob)).into_response()
+}
+
+/// Answers the latest revision of the charge, or the one in the `revisao` query param.
+async fn consultar_cob(
+    State(state): State<SharedState>,
+    Path(txid): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    let revisao = match suportados(&query, &["revisao"], false).and_then(|_| param::<i32>(&query, "revisao")) {
+        Ok(revisao) => revisao,
+        Err(detalhe) => return requisicao_invalida(detalhe),
+    };
+
+    let state = state.lock().unwrap();
+    let cob = match state.cobs.get(&txid) {
+        Some(cob) => cob,
+        None => return cob_nao_encontrada(),
+    };
+    let cob = match revisao {
+        Some(revisao) if Some(revisao) != cob.revisao => state
+            .revisoes
+            .get(&txid)
+            .and_then(|revisoes| revisoes.get(usize::try_from(revisao).ok()?)),
+        _ => Some(cob),
+    };
+
+    match cob {
+        Some(cob) => Json(cob).into_response(),
+        None => cob_nao_encontrada(),
+    }
+}
+
+/// Applies the revision as a JSON merge patch over the charge.
+async fn revisar_cob(
+    State(state): State<SharedState>,
+    Path(txid): Path<String>,
+    Json(revisao): Json<Value>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+    let state = &mut *state;
+    let cob = match state.cobs.get_mut(&txid) {
+        Some(cob) => cob,
+        None => return cob_nao_encontrada(),
+    };
+    if cob.status != Some(CobrancaStatus::ATIVA) {
+        return problema(
+            StatusCode::BAD_REQUEST,
+            "CobOperacaoInvalida",
+            "A cobrança não está ativa",
+        );
+    }
+
+    let mut revisada = serde_json::to_value(&*cob).expect("charges are always serializable");
+    merge_patch(&mut revisada, revisao);
+
+    match serde_json::from_value(revisada) {
+        Ok(revisada) => {
+            let anterior = std::mem::replace(cob, revisada);
+            state.revisoes.entry(txid).or_default().push(anterior);
+            cob.revisao = Some(cob.revisao.unwrap_or_default() + 1);
+            Json(&*cob).into_response()
+        }
+        Err(e) => problema(StatusCode::BAD_REQUEST, "CobOperacaoInvalida", &e.to_string()),
+    }
+}
+
+async fn listar_cobs(State(state): State<SharedState>, Query(query): Query<HashMap<String, String>>) -> Response {
+    let filtrar = || {
+        suportados(
+            &query,
+            &["inicio", "fim", "cpf", "cnpj", "locationPresente", "status"],
+            true,
+        )?;
+        let periodo = Periodo::from_query(&query)?;
+        let cpf = query.get("cpf");
+        let cnpj = query.get("cnpj");
+        let location_presente = param::<bool>(&query, "locationPresente")?;
+        let status = match query.get("status") {
+            Some(status) => Some(
+                serde_json::from_value::<CobrancaStatus>(Value::String(status.clone()))
+                    .map_err(|_| parametro_invalido("status"))?,
+            ),
+            None => None,
+        };
+
+        let state = state.lock().unwrap();
+        let cobs: Vec<_> = state
+            .cobs
+            .values()
+            .filter(|cob| {
+                let devedor = serde_json::to_value(&cob.devedor).expect("debtors are always serializable");
+                let documento = |tipo: &str| devedor.get(tipo).and_then(Value::as_str);
+
+                periodo.contem(cob.calendario.criacao.as_deref())
+                    && cpf.is_none_or(|cpf| documento("cpf") == Some(cpf.as_str()))
+                    && cnpj.is_none_or(|cnpj| documento("cnpj") == Some(cnpj.as_str()))
+                    && location_presente.is_none_or(|presente| cob.location.is_some() == presente)
+                    && status.is_none_or(|status| cob.status == Some(status))
+            })
+            .collect();
+
+        let (parametros, cobs) = paginar(cobs, &query)?;
+        Ok(Json(CobrancasImediatas { parametros, cobs }).into_response())
+    };
+
+    filtrar().unwrap_or_else(requisicao_invalida)
+}
+
+async fn listar_pix(State(state): State<SharedState>, Query(query): Query<HashMap<String, String>>) -> Response {
+    let filtrar = || {
+        suportados(
+            &query,
+            &["inicio", "fim", "txid", "txIdPresente", "devolucaoPresente"],
+            true,
+        )?;
+        let periodo = Periodo::from_query(&query)?;
+        let txid = query.get("txid");
+        let txid_presente = param::<bool>(&query, "txIdPresente")?;
+        let devolucao_presente = param::<bool>(&query, "devolucaoPresente")?;
+
+        let state = state.lock().unwrap();
+        let pix: Vec<_> = state
+            .pix
+            .iter()
+            .filter(|pix| {
+                let devolucoes = pix.devolucoes.as_ref().is_some_and(|devolucoes| !devolucoes.is_empty());
+
+                periodo.contem(Some(&pix.horario))
+                    && txid.is_none_or(|txid| pix.txid.as_ref() == Some(txid))
+                    && txid_presente.is_none_or(|presente| pix.txid.is_some() == presente)
+                    && devolucao_presente.is_none_or(|presente| devolucoes == presente)
+            })
+            .collect();
+
+        let (parametros, pix) = paginar(pix, &query)?;
+        Ok(Json(PixRecebidos { parametros, pix }).into_response())
+    };
+
+    filtrar().unwrap_or_else(requisicao_invalida)
+}
+
+async fn consultar_pix(State(state): State<SharedState>, Path(e2eid): Path<String>) -> Response {
+    let state = state.lock().unwrap();
+
+    match state.pix.iter().find(|pix| pix.end_to_end_id == e2eid) {
+        Some(pix) => Json(pix).into_response(),
+        None => problema(StatusCode::NOT_FOUND, "PixNaoEncontrado", "Pix não encontrado"),
+    }
+}
+
+async fn criar_webhook(
+    State(state): State<SharedState>,
+    Path(chave): Path<String>,
+    Json(payload): Json<Value>,
+) -> Response {
+    let webhook_url = match payload.get("webhookUrl").and_then(Value::as_str) {
+        Some(webhook_url) => webhook_url.to_string(),
+        None => return problema(StatusCode::BAD_REQUEST, "WebhookOperacaoInvalida", "webhookUrl ausente"),
+    };
+
+    let webhook = WebHookResponse {
+        webhook_url,
+        chave: chave.clone(),
+        criacao: now(),
+    };
+    state.lock().unwrap().webhooks.insert(chave, webhook);
+    StatusCode::NO_CONTENT.into_response()
+}
+
+async fn consultar_webhook(State(state): State<SharedState>, Path(chave): Path<String>) -> Response {
+    match state.lock().unwrap().webhooks.get(&chave) {
+        Some(webhook) => Json(webhook).into_response(),
+        None => webhook_nao_encontrado(),
+    }
+}
+
+async fn cancelar_webhook(State(state): State<SharedState>, Path(chave): Path<String>) -> Response {
+    match state.lock().unwrap().webhooks.remove(&chave) {
+        Some(_) => StatusCode::NO_CONTENT.into_response(),
+        None => webhook_nao_encontrado(),
+    }
+}
+
+async fn listar_webhooks(State(state): State<SharedState>, Query(query): Query<HashMap<String, String>>) -> Response {
+    let filtrar = || {
+        suportados(&query, &["inicio", "fim"], true)?;
+        let periodo = Periodo::from_query(&query)?;
+
+        let state = state.lock().unwrap();
+        let webhooks: Vec<_> = state
+            .webhooks
+            .values()
+            .filter(|webhook| periodo.contem(Some(&webhook.criacao)))
+            .collect();
+
+        let (parametros, webhooks) = paginar(webhooks, &query)?;
+        Ok(Json(WebHooks { parametros, webhooks }).into_response())
+    };
+
+    filtrar().unwrap_or_else(requisicao_invalida)
+}
+
+/// Rejects the query params that aren't `supported`, nor pagination params when `paginated`.
+fn suportados(query: &HashMap<String, String>, supported: &[&str], paginated: bool) -> Result<(), String> {
+    let paginacao = ["paginacao.paginaAtual", "paginacao.itensPorPagina"];
+    let suportado = |name: &str| supported.contains(&name) || (paginated && paginacao.contains(&name));
+
+    match query.keys().find(|name| !suportado(name)) {
+        Some(name) => Err(format!("O parâmetro {} não é suportado", name)),
+        None => Ok(()),
+    }
+}
+
+/// The query param `name`, parsed as `T`.
+fn param<T: FromStr>(query: &HashMap<String, String>, name: &str) -> Result<Option<T>, String> {
+    query
+        .get(name)
+        .map(|value| value.parse().map_err(|_| parametro_invalido(name)))
+        .transpose()
+}
+
+fn parametro_invalido(name: &str) -> String {
+    format!("O parâmetro {} tem um valor inválido", name)
+}
+
+fn requisicao_invalida(detalhe: String) -> Response {
+    problema(StatusCode::BAD_REQUEST, "RequisicaoInvalida", &detalhe)
+}
+
+/// The `inicio` and `fim` query params, both inclusive.
+struct Periodo {
+    inicio: Option<DateTime<Utc>>,
+    fim: Option<DateTime<Utc>>,
+}
+
+impl Periodo {
+    fn from_query(query: &HashMap<String, String>) -> Result<Periodo, String> {
+        Ok(Periodo {
+            inicio: param(query, "inicio")?,
+            fim: param(query, "fim")?,
+        })
+    }
+
+    /// Whether `horario`, in RFC 3339, is within the period. A missing `horario` is only within an unbounded period.
+    fn contem(&self, horario: Option<&str>) -> bool {
+        let horario = horario.and_then(|horario| horario.parse::<DateTime<Utc>>().ok());
+
+        match horario {
+            Some(horario) => {
+                self.inicio.is_none_or(|inicio| inicio <= horario) && self.fim.is_none_or(|fim| horario <= fim)
+            }
+            None => self.inicio.is_none() && self.fim.is_none(),
+        }
+    }
+}
+
+/// Slices `items` according to the `paginacao.*` query params.
+fn paginar<T: Clone>(items: Vec<&T>, query: &HashMap<String, String>) -> Result<(Parametros, Vec<T>), String> {
+    let pagina_atual: i32 = param(query, "paginacao.paginaAtual")?.unwrap_or(0);
+    let itens_por_pagina: i32 = param(query, "paginacao.itensPorPagina")?.unwrap_or(100);
+    if pagina_atual < 0 {
+        return Err(parametro_invalido("paginacao.paginaAtual"));
+    }
+    if itens_por_pagina < 1 {
+        return Err(parametro_invalido("paginacao.itensPorPagina"));
+    }
+
+    let total = items.len() as i32;
+    let pagina: Vec<T> = items
+        .into_iter()
+        .skip((pagina_atual as usize).saturating_mul(itens_por_pagina as usize))
+        .take(itens_por_pagina as usize)
+        .cloned()
+        .collect();
+
+    let parametros = Parametros {
+        inicio: query.get("inicio").cloned().unwrap_or_default(),
+        fim: query.get("fim").cloned().unwrap_or_default(),
+        paginacao: Paginacao {
+            pagina_atual,
+            itens_por_pagina,
+            quantidade_de_paginas: ((total as i64 + itens_por_pagina as i64 - 1) / itens_por_pagina as i64) as i32,
+            quantidade_total_de_itens: total,
+        },
+    };
+    Ok((parametros, pagina))
+}
+
+/// RFC 7396 JSON merge patch.
+fn merge_patch(target: &mut Value, patch: Value) {
+    match patch {
+        Value::Object(patch) => {
+            if !target.is_object() {
+                *target = json!({});
+            }
+            let target = target.as_object_mut().unwrap();
+
+            for (key, value) in patch {
+                if value.is_null() {
+                    target.remove(&key);
+                } else {
+                    merge_patch(target.entry(key).or_insert(Value::Null), value);
+                }
+            }
+        }
+        patch => *target = patch,
+    }
+}
+
+fn problema(status: StatusCode, tipo: &str, detalhe: &str) -> Response {
+    let problema = Problema {
+        tipo: format!("https://pix.example.com/api/v2/error/{}", tipo),
+        titulo: tipo.to_string(),
+        status: status.as_u16(),
+        detalhe: Some(detalhe.to_string()),
+        correlation_id: None,
+        violacoes: None,
+    };
+    (status, Json(problema)).into_response()
+}
+
+fn cob_nao_encontrada() -> Response {
+    problema(StatusCode::NOT_FOUND, "CobNaoEncontrado", "Cobrança não encontrada")
+}
+
+fn webhook_nao_encontrado() -> Response {
+    problema(StatusCode::NOT_FOUND, "WebhookNaoEncontrado", "Webhook não encontrado")
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn random_id(length: usize) -> String {
+    std::iter::repeat_with(fastrand::alphanumeric).take(length).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::cob::{CobFiltro, Devedor};
+    use crate::pix::PixFiltro;
+    use crate::Executor;
+
+    const CHAVE: &str = "7d9f0335-8dcc-4054-9bf9-0dbd61d36906";
+
+    fn cobranca() -> CobrancaImediata {
+        let devedor = Devedor::new_pessoa_fisica("12345678909".to_string(), "Francisco da Silva".to_string());
+        CobrancaImediata::new(10.25, CHAVE.to_string(), devedor)
+    }
+
+    #[tokio::test]
+    async fn t_cobranca_paga_dispara_webhook() {
+        let psp = MockPsp::start().await.unwrap();
+        let client = psp.client();
+
+        let receiver = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/webhook/pix"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&receiver)
+            .await;
+
+        client
+            .webhook()
+            .criar_por_chave(CHAVE.to_string(), format!("{}/webhook", receiver.uri()))
+            .execute()
+            .await
+            .unwrap();
+
+        let txid = CobTxid::from_reference("pedido-4815");
+        let cobranca = client
+            .cob()
+            .criar_cobranca_txid(&txid, cobranca())
+            .execute()
+            .await
+            .unwrap();
+        let txid = String::from(txid);
+        assert_eq!(cobranca.txid.as_ref(), Some(&txid));
+        assert_eq!(cobranca.status, Some(CobrancaStatus::ATIVA));
+
+        let pix = psp.simulate_payment(&txid).await.unwrap();
+        assert_eq!(pix.valor, "10.25");
+
+        let recebido = client
+            .pix()
+            .consultar(pix.end_to_end_id.clone())
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(recebido.txid.as_deref(), Some(&*txid));
+        assert_eq!(psp.cobrancas()[0].status, Some(CobrancaStatus::CONCLUIDA));
+        assert_eq!(psp.issued_tokens(), 1);
+    }
+
+    #[tokio::test]
+    async fn t_cobranca_cancelada_nao_pode_ser_paga() {
+        let psp = MockPsp::start().await.unwrap();
+        let client = psp.client();
+
+        let cobranca = client
+            .cob()
+            .criar_cobranca_imediata(cobranca())
+            .execute()
+            .await
+            .unwrap();
+        let txid = CobTxid::new(cobranca.txid.unwrap()).unwrap();
+
+        let revisada = client.cob().alterar_valor(&txid, 20.0).execute().await.unwrap();
+        assert_eq!(revisada.valor.original, "20.00");
+        assert_eq!(revisada.devedor, cobranca.devedor);
+        assert_eq!(revisada.revisao, Some(1));
+
+        let cancelada = client.cob().cancelar(&txid).execute().await.unwrap();
+        assert_eq!(cancelada.status, Some(CobrancaStatus::REMOVIDA_PELO_USUARIO_RECEBEDOR));
+        assert!(psp.simulate_payment(txid.as_str()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn t_falhas_programadas() {
+        let psp = MockPsp::start().await.unwrap();
+        let client = psp.client();
+
+        client
+            .cob()
+            .criar_cobranca_imediata(cobranca())
+            .execute()
+            .await
+            .unwrap();
+        psp.fail(Method::GET, "/cob", StatusCode::SERVICE_UNAVAILABLE, 1);
+        psp.fail(Method::POST, "/cob", StatusCode::SERVICE_UNAVAILABLE, 1);
+
+        // GET is retried by the default retry policy, POST is not
+        let filtro = CobFiltro::new(Utc::now() - chrono::Duration::days(1), Utc::now());
+        let cobrancas = client.cob().consultar_cobrancas(filtro).execute().await.unwrap();
+        assert_eq!(cobrancas.cobs.len(), 1);
+
+        let result = client.cob().criar_cobranca_imediata(cobranca()).execute().await;
+        assert!(matches!(result, Err(PixError::ServiceUnavailable(_))));
+    }
+
+    #[test]
+    fn t_falha_por_segmento() {
+        assert!(within("/cob", "/cob"));
+        assert!(within("/cob/7978c0c97ea847e78e8849634473c1f1", "/cob"));
+        assert!(within("/cob/7978c0c97ea847e78e8849634473c1f1", "/cob/"));
+        assert!(!within("/cobv", "/cob"));
+        assert!(!within("/cob", "/co"));
+    }
+
+    #[tokio::test]
+    async fn t_listagens_filtradas() {
+        let psp = MockPsp::start().await.unwrap();
+        let client = psp.client();
+        let inicio = Utc::now() - chrono::Duration::minutes(1);
+
+        let outro_devedor = Devedor::new_pessoa_juridica("12345678000195".to_string(), "Empresa".to_string());
+        let mut txids = Vec::new();
+        for cobranca in [cobranca(), CobrancaImediata::new(5.0, CHAVE.to_string(), outro_devedor)] {
+            let cobranca = client.cob().criar_cobranca_imediata(cobranca).execute().await.unwrap();
+            txids.push(cobranca.txid.unwrap());
+        }
+        psp.simulate_payment(&txids[0]).await.unwrap();
+        let fim = Utc::now() + chrono::Duration::minutes(1);
+
+        let listar = |filtro: CobFiltro| async {
+            let cobs = client.cob().consultar_cobrancas(filtro).execute().await.unwrap().cobs;
+            cobs.into_iter().map(|cob| cob.txid.unwrap()).collect::<Vec<_>>()
+        };
+        let concluidas = listar(CobFiltro::new(inicio, fim).status(CobrancaStatus::CONCLUIDA)).await;
+        assert_eq!(concluidas, [txids[0].clone()]);
+        let por_cnpj = listar(CobFiltro::new(inicio, fim).cnpj("12345678000195".to_string())).await;
+        assert_eq!(por_cnpj, [txids[1].clone()]);
+        assert!(listar(CobFiltro::new(fim, fim)).await.is_empty());
+
+        let pix = client
+            .pix()
+            .consultar_recebidos(PixFiltro::new(inicio, fim).txid(txids[1].clone()))
+            .execute()
+            .await
+            .unwrap();
+        assert!(pix.pix.is_empty());
+        let pix = client
+            .pix()
+            .consultar_recebidos(PixFiltro::new(inicio, fim).devolucao_presente(false))
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(pix.pix[0].txid.as_ref(), Some(&txids[0]));
+
+        // the payer isn't recorded, so filtering by it is rejected instead of ignored
+        let result = client
+            .pix()
+            .consultar_recebidos(PixFiltro::new(inicio, fim).cpf("12345678909".to_string()))
+            .execute()
+            .await;
+        assert!(matches!(result, Err(PixError::PayloadError(_))));
+    }
+
+    #[tokio::test]
+    async fn t_consultar_revisao() {
+        let psp = MockPsp::start().await.unwrap();
+        let client = psp.client();
+
+        let cobranca = client
+            .cob()
+            .criar_cobranca_imediata(cobranca())
+            .execute()
+            .await
+            .unwrap();
+        let txid = CobTxid::new(cobranca.txid.unwrap()).unwrap();
+        client.cob().alterar_valor(&txid, 20.0).execute().await.unwrap();
+
+        let consultar = |revisao| client.cob().consultar_cobranca_txid(&txid, revisao).execute();
+        assert_eq!(consultar(Some(0)).await.unwrap().valor.original, "10.25");
+        assert_eq!(consultar(Some(1)).await.unwrap().valor.original, "20.00");
+        assert_eq!(consultar(None).await.unwrap().revisao, Some(1));
+        assert!(matches!(consultar(Some(2)).await, Err(PixError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn t_autenticacao() {
+        let psp = MockPsp::start().await.unwrap();
+
+        let invalido = PixClient::builder(&psp.uri())
+            .https_only(false)
+            .token_manager(TokenManager::basic(CLIENT_ID, "outro-segredo"))
+            .build()
+            .unwrap();
+        let result = invalido.cob().criar_cobranca_imediata(cobranca()).execute().await;
+        assert!(matches!(result, Err(PixError::InvalidCredentials(_))));
+        assert_eq!(psp.issued_tokens(), 0);
+
+        let http = reqwest::Client::new();
+        let grant_type = http
+            .post(format!("{}/oauth/token", psp.uri()))
+            .basic_auth(CLIENT_ID, Some(CLIENT_SECRET))
+            .form(&[("grant_type", "authorization_code")])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(grant_type.status(), StatusCode::BAD_REQUEST);
+
+        let form = http
+            .post(format!("{}/oauth/token", psp.uri()))
+            .basic_auth(CLIENT_ID, Some(CLIENT_SECRET))
+            .form(&[("grant_type", "client_credentials")])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(form.status(), StatusCode::OK);
+
+        // only tokens issued by the PSP are accepted
+        let forjado = http
+            .get(format!("{}/cob/{}", psp.uri(), CobTxid::random()))
+            .bearer_auth("mock-token-42")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(forjado.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn t_token_expirado_e_renovado() {
+        let psp = MockPsp::start().await.unwrap();
+        let client = psp.client();
+
+        let cobranca = client
+            .cob()
+            .criar_cobranca_imediata(cobranca())
+            .execute()
+            .await
+            .unwrap();
+        let txid = CobTxid::new(cobranca.txid.unwrap()).unwrap();
+
+        // the rejected request is retried once with a new token
+        psp.expire_tokens();
+        client
+            .cob()
+            .consultar_cobranca_txid(&txid, None)
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(psp.issued_tokens(), 2);
+
+        psp.fail(Method::GET, "/cob", StatusCode::UNAUTHORIZED, 1);
+        client
+            .cob()
+            .consultar_cobranca_txid(&txid, None)
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(psp.issued_tokens(), 3);
+
+        psp.fail(Method::POST, "/oauth/token", StatusCode::UNAUTHORIZED, 1);
+        psp.expire_tokens();
+        let result = client.cob().consultar_cobranca_txid(&txid, None).execute().await;
+        assert!(matches!(result, Err(PixError::InvalidCredentials(_))));
+    }
+}
@@ -0,0 +1,364 @@
+//! Tipos compartilhados pelos endpoints de listagem.
+//!
+//! Toda listagem retorna um bloco `parametros.paginacao`. Requisições cujas respostas implementam [`Paginado`]
+//! podem ser convertidas em um [`Stream`] com [`ApiRequest::into_stream`], que percorre todas as páginas
+//! transparentemente.
+//!
+//! # Example: Walk through every charge created in the last day
+//!
+//! ```no_run
+//! use chrono::{Duration, Utc};
+//! use futures_util::TryStreamExt;
+//! use pix_api_client::cob::CobFiltro;
+//! use pix_api_client::PixClient;
+//!
+//! # async fn doc_test(pix_client: PixClient) -> Result<(), anyhow::Error> {
+//! let filtro = CobFiltro::new(Utc::now() - Duration::days(1), Utc::now());
+//!
+//! let mut cobrancas = Box::pin(pix_client.cob().consultar_cobrancas(filtro).into_stream());
+//! while let Some(cobranca) = cobrancas.try_next().await? {
+//!     println!("{:?}", cobranca.txid);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use futures_util::stream::{self, Stream, StreamExt, TryStreamExt};
+use reqwest::Request;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{ApiResult, PixError};
+use crate::{ApiRequest, Executor};
+
+const PAGINA_ATUAL: &str = "paginacao.paginaAtual";
+
+/// Bloco `parametros` retornado por todos os endpoints de listagem.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Parametros {
+    pub inicio: String,
+    pub fim: String,
+    pub paginacao: Paginacao,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Paginacao {
+    /// Página atual, iniciando em 0.
+    #[serde(rename = "paginaAtual")]
+    pub pagina_atual: i32,
+    #[serde(rename = "itensPorPagina")]
+    pub itens_por_pagina: i32,
+    #[serde(rename = "quantidadeDePaginas")]
+    pub quantidade_de_paginas: i32,
+    #[serde(rename = "quantidadeTotalDeItens")]
+    pub quantidade_total_de_itens: i32,
+}
+
+/// Parâmetros de paginação aceitos na query string dos endpoints de listagem.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PaginacaoParams {
+    #[serde(rename = "paginacao.paginaAtual")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pagina_atual: Option<i32>,
+    #[serde(rename = "paginacao.itensPorPagina")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub itens_por_pagina: Option<i32>,
+}
+
+impl PaginacaoParams {
+    pub fn new(pagina_atual: i32, itens_por_pagina: i32) -> Self {
+        Self {
+            pagina_atual: Some(pagina_atual),
+            itens_por_pagina: Some(itens_por_pagina),
+        }
+    }
+}
+
+impl Paginacao {
+    /// Índice da próxima página, caso exista.
+    pub fn proxima_pagina(&self) -> Option<i32> {
+        let proxima = self.pagina_atual + 1;
+        if proxima < self.quantidade_de_paginas {
+            Some(proxima)
+        } else {
+            None
+        }
+    }
+}
+
+/// Implementado pelas respostas de endpoints de listagem.
+pub trait Paginado: DeserializeOwned {
+    type Item;
+
+    fn parametros(&self) -> &Parametros;
+
+    fn into_items(self) -> Vec<Self::Item>;
+}
+
+impl<'a, R> ApiRequest<'a, R>
+where
+    R: Paginado + Send + 'static,
+    R::Item: 'a,
+{
+    /// Percorre todas as páginas da listagem, a partir da página informada no filtro, retornando cada item
+    /// individualmente. O stream é encerrado no primeiro erro.
+    ///
+    /// Cada página é requisitada com os mesmos headers, política de retentativas e autorização desta requisição. Caso
+    /// o PSP retorne uma página diferente da requisitada, o stream é encerrado com erro, em vez de repetir páginas.
+    pub fn into_stream(self) -> impl Stream<Item = ApiResult<R::Item>> + 'a {
+        let ApiRequest {
+            client,
+            request,
+            managed_authorization,
+            retry_policy,
+            ..
+        } = self;
+
+        let pagina_inicial = pagina_solicitada(&request);
+
+        stream::try_unfold(Some((request, pagina_inicial)), move |state| {
+            let retry_policy = retry_policy.clone();
+
+            async move {
+                let (request, pagina_solicitada) = match state {
+                    Some(state) => state,
+                    None => return Ok::<_, PixError>(None),
+                };
+
+                let next_request = request.try_clone();
+                let page = ApiRequest::<R> {
+                    managed_authorization,
+                    retry_policy,
+                    ..ApiRequest::new(client, request)
+                }
+                .execute()
+                .await?;
+
+                let paginacao = &page.parametros().paginacao;
+                if paginacao.pagina_atual != pagina_solicitada {
+                    return Err(PixError::Other(format!(
+                        "The PSP returned page {} when page {} was requested",
+                        paginacao.pagina_atual, pagina_solicitada
+                    )));
+                }
+
+                let proxima = pagina_solicitada + 1;
+                let next_state = if proxima < paginacao.quantidade_de_paginas {
+                    let request = next_request.ok_or_else(|| {
+                        PixError::Other("The request can't be cloned to fetch the next page".to_string())
+                    })?;
+                    Some((com_pagina(request, proxima), proxima))
+                } else {
+                    None
+                };
+
+                Ok(Some((page.into_items(), next_state)))
+            }
+        })
+        .map_ok(|items| stream::iter(items).map(Ok))
+        .try_flatten()
+    }
+}
+
+/// Valor do parâmetro `paginacao.paginaAtual` da query string, ou a primeira página quando ausente.
+fn pagina_solicitada(request: &Request) -> i32 {
+    request
+        .url()
+        .query_pairs()
+        .find(|(key, _)| key == PAGINA_ATUAL)
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Substitui o parâmetro `paginacao.paginaAtual` da query string.
+fn com_pagina(mut request: Request, pagina: i32) -> Request {
+    let query: Vec<(String, String)> = request
+        .url()
+        .query_pairs()
+        .filter(|(key, _)| key != PAGINA_ATUAL)
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+
+    request
+        .url_mut()
+        .query_pairs_mut()
+        .clear()
+        .extend_pairs(query)
+        .append_pair(PAGINA_ATUAL, &pagina.to_string());
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+    use futures_util::TryStreamExt;
+    use reqwest::header::HeaderMap;
+    use reqwest::{Client, Method};
+    use serde_json::{json, Value};
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::cob::CobFiltro;
+    use crate::retry::RetryPolicy;
+    use crate::PixClient;
+
+    fn pagina(pagina_atual: i32, txids: &[&str]) -> Value {
+        let cobs: Vec<Value> = txids
+            .iter()
+            .map(|txid| {
+                json!({
+                    "calendario": { "criacao": "2020-09-09T20:15:00.358Z", "expiracao": 3600 },
+                    "txid": txid,
+                    "devedor": { "cpf": "12345678909", "nome": "Francisco da Silva" },
+                    "valor": { "original": "10.00" },
+                    "chave": "a1f4102e-a446-4a57-bcce-6fa48899c1d1"
+                })
+            })
+            .collect();
+
+        json!({
+            "parametros": {
+                "inicio": "2020-04-01T00:00:00Z",
+                "fim": "2020-04-02T00:00:00Z",
+                "paginacao": {
+                    "paginaAtual": pagina_atual,
+                    "itensPorPagina": 2,
+                    "quantidadeDePaginas": 3,
+                    "quantidadeTotalDeItens": 5
+                }
+            },
+            "cobs": cobs
+        })
+    }
+
+    async fn mount_pagina(server: &MockServer, pagina_atual: i32, response: ResponseTemplate) {
+        Mock::given(method("GET"))
+            .and(path("/cob"))
+            .and(query_param(PAGINA_ATUAL, pagina_atual.to_string()))
+            .respond_with(response)
+            .mount(server)
+            .await;
+    }
+
+    fn stream_de_cobrancas(client: &PixClient) -> impl Stream<Item = ApiResult<String>> + '_ {
+        let inicio = Utc.with_ymd_and_hms(2020, 4, 1, 0, 0, 0).unwrap();
+        let filtro = CobFiltro::new(inicio, inicio + chrono::Duration::days(1)).paginacao(PaginacaoParams::new(0, 2));
+
+        client
+            .cob()
+            .consultar_cobrancas(filtro)
+            .retry_policy(
+                RetryPolicy::default()
+                    .base_delay(Duration::from_millis(10))
+                    .jitter(false),
+            )
+            .into_stream()
+            .map_ok(|cobranca| cobranca.txid.unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn t_stream_percorre_todas_as_paginas() {
+        let server = MockServer::start().await;
+        mount_pagina(
+            &server,
+            0,
+            ResponseTemplate::new(200).set_body_json(pagina(0, &["a", "b"])),
+        )
+        .await;
+        // a política de retentativas da requisição também vale para as páginas seguintes
+        Mock::given(method("GET"))
+            .and(path("/cob"))
+            .and(query_param(PAGINA_ATUAL, "1"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        mount_pagina(
+            &server,
+            1,
+            ResponseTemplate::new(200).set_body_json(pagina(1, &["c", "d"])),
+        )
+        .await;
+        mount_pagina(&server, 2, ResponseTemplate::new(200).set_body_json(pagina(2, &["e"]))).await;
+
+        let client = PixClient::from_client(Client::new(), &server.uri(), HeaderMap::new())
+            .with_retry_policy(RetryPolicy::none());
+        let txids: Vec<String> = stream_de_cobrancas(&client).try_collect().await.unwrap();
+
+        assert_eq!(txids, ["a", "b", "c", "d", "e"]);
+        // nenhuma página além da última `paginaAtual` é requisitada
+        assert_eq!(server.received_requests().await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn t_stream_retorna_erro_de_pagina_intermediaria() {
+        let server = MockServer::start().await;
+        mount_pagina(
+            &server,
+            0,
+            ResponseTemplate::new(200).set_body_json(pagina(0, &["a", "b"])),
+        )
+        .await;
+        mount_pagina(&server, 1, ResponseTemplate::new(400)).await;
+        mount_pagina(&server, 2, ResponseTemplate::new(200).set_body_json(pagina(2, &["e"]))).await;
+
+        let client = PixClient::from_client(Client::new(), &server.uri(), HeaderMap::new());
+        let resultados: Vec<ApiResult<String>> = stream_de_cobrancas(&client).collect().await;
+
+        assert_eq!(resultados.len(), 3);
+        assert_eq!(resultados[0].as_deref().unwrap(), "a");
+        assert_eq!(resultados[1].as_deref().unwrap(), "b");
+        assert!(matches!(resultados[2], Err(PixError::PayloadError(_))));
+    }
+
+    #[tokio::test]
+    async fn t_stream_interrompido_quando_psp_ignora_pagina() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/cob"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(pagina(0, &["a", "b"])))
+            .mount(&server)
+            .await;
+
+        let client = PixClient::from_client(Client::new(), &server.uri(), HeaderMap::new());
+        let resultados: Vec<ApiResult<String>> = stream_de_cobrancas(&client).collect().await;
+
+        assert_eq!(resultados.len(), 3);
+        assert!(matches!(&resultados[2], Err(PixError::Other(e)) if e.contains("page 0 when page 1")));
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn t_proxima_pagina() {
+        let request = Client::new()
+            .request(Method::GET, "https://pix.example.com/cob")
+            .query(&[("inicio", "2020-04-01T00:00:00Z"), (PAGINA_ATUAL, "0")])
+            .build()
+            .unwrap();
+
+        let paginacao = Paginacao {
+            pagina_atual: 0,
+            itens_por_pagina: 100,
+            quantidade_de_paginas: 2,
+            quantidade_total_de_itens: 150,
+        };
+        let proxima = paginacao.proxima_pagina().unwrap();
+        let request = com_pagina(request, proxima);
+
+        assert_eq!(
+            request.url().query(),
+            Some("inicio=2020-04-01T00%3A00%3A00Z&paginacao.paginaAtual=1")
+        );
+        assert_eq!(
+            Paginacao {
+                pagina_atual: 1,
+                ..paginacao
+            }
+            .proxima_pagina(),
+            None
+        );
+    }
+}
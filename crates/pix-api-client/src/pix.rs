@@ -0,0 +1,226 @@
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use crate::paginacao::{PaginacaoParams, Paginado, Parametros};
+use crate::webhook::{Devolucoes, NaturezaDevolucao, PixInput};
+use crate::{ApiRequest, PixClient};
+
+pub struct PixEndpoint<'a> {
+    inner: &'a PixClient,
+}
+
+impl PixClient {
+    pub fn pix(&self) -> PixEndpoint<'_> {
+        PixEndpoint { inner: self }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PixRecebidos {
+    pub parametros: Parametros,
+    pub pix: Vec<PixInput>,
+}
+
+impl Paginado for PixRecebidos {
+    type Item = PixInput;
+
+    fn parametros(&self) -> &Parametros {
+        &self.parametros
+    }
+
+    fn into_items(self) -> Vec<PixInput> {
+        self.pix
+    }
+}
+
+/// Payload utilizado para solicitar uma devolução.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DevolucaoPayload {
+    pub valor: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub natureza: Option<NaturezaDevolucao>,
+    /// Mensagem ao pagador, com no máximo 140 caracteres.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub descricao: Option<String>,
+}
+
+impl DevolucaoPayload {
+    // Valor com no máximo duas casas decimais. Caso houver mais que 2 casas, o valor será truncado.
+    pub fn new(valor: f64) -> Self {
+        Self {
+            valor: format!("{:.2}", valor),
+            natureza: None,
+            descricao: None,
+        }
+    }
+
+    pub fn natureza(mut self, natureza: NaturezaDevolucao) -> Self {
+        self.natureza = Some(natureza);
+        self
+    }
+
+    pub fn descricao(mut self, descricao: String) -> Self {
+        self.descricao = Some(descricao);
+        self
+    }
+}
+
+/// Filtros aceitos pela listagem de Pix recebidos.
+///
+/// Os campos `cpf` e `cnpj` são mutuamente exclusivos, e o último informado prevalece.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PixFiltro {
+    pub inicio: DateTime<Utc>,
+    pub fim: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txid: Option<String>,
+    #[serde(rename = "txIdPresente")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txid_presente: Option<bool>,
+    #[serde(rename = "devolucaoPresente")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub devolucao_presente: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpf: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cnpj: Option<String>,
+    #[serde(flatten)]
+    pub paginacao: PaginacaoParams,
+}
+
+impl PixFiltro {
+    pub fn new(inicio: DateTime<Utc>, fim: DateTime<Utc>) -> Self {
+        Self {
+            inicio,
+            fim,
+            txid: None,
+            txid_presente: None,
+            devolucao_presente: None,
+            cpf: None,
+            cnpj: None,
+            paginacao: Default::default(),
+        }
+    }
+
+    pub fn txid(mut self, txid: String) -> Self {
+        self.txid = Some(txid);
+        self
+    }
+
+    pub fn txid_presente(mut self, txid_presente: bool) -> Self {
+        self.txid_presente = Some(txid_presente);
+        self
+    }
+
+    pub fn devolucao_presente(mut self, devolucao_presente: bool) -> Self {
+        self.devolucao_presente = Some(devolucao_presente);
+        self
+    }
+
+    pub fn cpf(mut self, cpf: String) -> Self {
+        self.cpf = Some(cpf);
+        self.cnpj = None;
+        self
+    }
+
+    pub fn cnpj(mut self, cnpj: String) -> Self {
+        self.cnpj = Some(cnpj);
+        self.cpf = None;
+        self
+    }
+
+    pub fn paginacao(mut self, paginacao: PaginacaoParams) -> Self {
+        self.paginacao = paginacao;
+        self
+    }
+}
+
+impl<'a> PixEndpoint<'a> {
+    /// Consulta um Pix recebido pelo seu `endToEndId`.
+    pub fn consultar(&self, e2eid: String) -> ApiRequest<'a, PixInput> {
+        let endpoint = format!("{}/pix/{}", self.inner.base_endpoint, e2eid);
+        self.inner.request_with_query(Method::GET, &endpoint, None::<()>)
+    }
+
+    /// Lista os Pix recebidos no período. Útil para recuperar eventos de webhooks que não foram entregues.
+    pub fn consultar_recebidos(&self, filtro: PixFiltro) -> ApiRequest<'a, PixRecebidos> {
+        let endpoint = format!("{}/pix", self.inner.base_endpoint);
+        self.inner.request_with_query(Method::GET, &endpoint, Some(filtro))
+    }
+
+    /// Solicita uma devolução do Pix identificado por `e2eid`.
+    /// O `id` é gerado pelo cliente, e identifica unicamente esta devolução.
+    pub fn solicitar_devolucao(
+        &self,
+        e2eid: String,
+        id: String,
+        payload: DevolucaoPayload,
+    ) -> ApiRequest<'a, Devolucoes> {
+        let endpoint = format!("{}/pix/{}/devolucao/{}", self.inner.base_endpoint, e2eid, id);
+        self.inner.request_with_headers(Method::PUT, &endpoint, payload)
+    }
+
+    pub fn consultar_devolucao(&self, e2eid: String, id: String) -> ApiRequest<'a, Devolucoes> {
+        let endpoint = format!("{}/pix/{}/devolucao/{}", self.inner.base_endpoint, e2eid, id);
+        self.inner.request_with_query(Method::GET, &endpoint, None::<()>)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhook::StatusDevolucao;
+
+    #[test]
+    fn t_pix_recebidos() {
+        let response = r#"{
+            "parametros": {
+                "inicio": "2020-04-01T00:00:00Z",
+                "fim": "2020-04-01T23:59:59Z",
+                "paginacao": {
+                    "paginaAtual": 0,
+                    "itensPorPagina": 100,
+                    "quantidadeDePaginas": 1,
+                    "quantidadeTotalDeItens": 1
+                }
+            },
+            "pix": [
+                {
+                    "endToEndId": "E12345678202009091221abcdef12345",
+                    "txid": "cd1fe328c875481285a6f233ae41b662",
+                    "valor": "100.00",
+                    "horario": "2020-09-10T13:03:33.902Z",
+                    "infoPagador": "Reforma da casa",
+                    "chave": "7d9f0335-8dcc-4054-9bf9-0dbd61d36906"
+                }
+            ]
+        }"#;
+
+        let recebidos: PixRecebidos = serde_json::from_str(response).unwrap();
+
+        assert_eq!(recebidos.parametros.paginacao.quantidade_total_de_itens, 1);
+        assert_eq!(recebidos.pix[0].end_to_end_id, "E12345678202009091221abcdef12345");
+    }
+
+    #[test]
+    fn t_devolucao_nao_realizada() {
+        let response = r#"{
+            "id": "123456",
+            "rtrId": "D12345678202009091000abcde123456",
+            "valor": "7.89",
+            "natureza": "MED_FRAUDE",
+            "horario": {
+                "solicitacao": "2020-09-11T15:25:59.411Z"
+            },
+            "status": "NAO_REALIZADO",
+            "motivo": "Saldo insuficiente"
+        }"#;
+
+        let devolucao: Devolucoes = serde_json::from_str(response).unwrap();
+
+        assert_eq!(devolucao.natureza, Some(NaturezaDevolucao::MED_FRAUDE));
+        assert_eq!(devolucao.status, StatusDevolucao::NAO_REALIZADO);
+        assert_eq!(devolucao.horario.liquidacao, None);
+    }
+}
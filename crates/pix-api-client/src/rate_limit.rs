@@ -0,0 +1,230 @@
+//! Client-side rate limiting, for PSP's that enforce request quotas per client.
+//!
+//! Requests are grouped by the first path segment after the base endpoint, such as `cob`, `pix` or `webhook`. Each
+//! group configured with a [`Quota`] has its own token bucket, and groups without one share the default quota, if
+//! any. A request that exceeds its quota waits until a token is available, instead of being rejected with `429`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use std::fs;
+//! use pix_api_client::rate_limit::{Quota, RateLimiter};
+//! use pix_api_client::PixClient;
+//!
+//! # fn teste() -> Result<(), anyhow::Error> {
+//! let rate_limiter = RateLimiter::new()
+//!     .default_quota(Quota::per_second(10))
+//!     .group("cob", Quota::per_minute(120));
+//!
+//! let pix_client = PixClient::builder("https://my-pix-h")
+//!     .pkcs12_identity(fs::read("my_cert.p12")?, "")
+//!     .rate_limiter(rate_limiter)
+//!     .build()?;
+//!
+//! // later on, check how much time was spent waiting for the quota
+//! if let Some(rate_limiter) = pix_client.rate_limiter() {
+//!     println!("{:?}", rate_limiter.metrics());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Amount of requests allowed within a period. The whole amount may be used in a single burst.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quota {
+    requests: u32,
+    period: Duration,
+}
+
+impl Quota {
+    pub fn new(requests: u32, period: Duration) -> Quota {
+        Self {
+            requests: requests.max(1),
+            period,
+        }
+    }
+
+    pub fn per_second(requests: u32) -> Quota {
+        Self::new(requests, Duration::from_secs(1))
+    }
+
+    pub fn per_minute(requests: u32) -> Quota {
+        Self::new(requests, Duration::from_secs(60))
+    }
+
+    /// Interval in which a single token is refilled.
+    fn interval(&self) -> Duration {
+        self.period / self.requests
+    }
+}
+
+/// How a group of endpoints was affected by its quota.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitMetrics {
+    /// Requests that went through the bucket.
+    pub requests: u64,
+    /// Requests that had to wait for a token.
+    pub throttled: u64,
+    pub total_wait: Duration,
+    pub max_wait: Duration,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    quota: Quota,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    /// Negative when there are requests waiting for a token.
+    tokens: f64,
+    updated_at: Instant,
+    metrics: RateLimitMetrics,
+}
+
+impl Bucket {
+    fn new(quota: Quota) -> Bucket {
+        Self {
+            quota,
+            state: Mutex::new(BucketState {
+                tokens: quota.requests as f64,
+                updated_at: Instant::now(),
+                metrics: Default::default(),
+            }),
+        }
+    }
+
+    /// Reserves a token, returning how long to wait before it can be used.
+    fn reserve(&self) -> Duration {
+        let mut state = self.state.lock().unwrap();
+        let interval = self.quota.interval().as_secs_f64();
+
+        let now = Instant::now();
+        let refilled = now.duration_since(state.updated_at).as_secs_f64() / interval;
+        state.tokens = (state.tokens + refilled).min(self.quota.requests as f64) - 1.0;
+        state.updated_at = now;
+
+        let wait = if state.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-state.tokens * interval)
+        };
+
+        let metrics = &mut state.metrics;
+        metrics.requests += 1;
+        if !wait.is_zero() {
+            metrics.throttled += 1;
+            metrics.total_wait += wait;
+            metrics.max_wait = metrics.max_wait.max(wait);
+        }
+        wait
+    }
+}
+
+/// Token buckets for each group of endpoints.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    default: Option<Bucket>,
+    groups: HashMap<String, Bucket>,
+}
+
+impl RateLimiter {
+    /// A limiter without any quota, which never delays a request.
+    pub fn new() -> RateLimiter {
+        Self::default()
+    }
+
+    /// Quota shared by every group without its own quota.
+    pub fn default_quota(mut self, quota: Quota) -> RateLimiter {
+        self.default = Some(Bucket::new(quota));
+        self
+    }
+
+    /// Quota of the endpoints under `/{group}`, such as `cob`, `cobv`, `pix` or `webhook`.
+    pub fn group(mut self, group: &str, quota: Quota) -> RateLimiter {
+        self.groups.insert(group.to_string(), Bucket::new(quota));
+        self
+    }
+
+    /// Metrics of each configured group. The default quota is reported under `"*"`.
+    pub fn metrics(&self) -> HashMap<String, RateLimitMetrics> {
+        let groups = self.groups.iter().map(|(group, bucket)| (group.as_str(), bucket));
+
+        self.default
+            .iter()
+            .map(|bucket| ("*", bucket))
+            .chain(groups)
+            .map(|(group, bucket)| (group.to_string(), bucket.state.lock().unwrap().metrics))
+            .collect()
+    }
+
+    /// Waits until the quota of `group` allows another request.
+    pub(crate) async fn acquire(&self, group: &str) {
+        let bucket = match self.groups.get(group).or(self.default.as_ref()) {
+            Some(bucket) => bucket,
+            None => return,
+        };
+
+        let wait = bucket.reserve();
+        if !wait.is_zero() {
+            tracing::debug!(group, ?wait, "rate limit reached, waiting");
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// First path segment of `url` after `base_endpoint`.
+pub(crate) fn endpoint_group<'u>(base_endpoint: &str, url: &'u str) -> &'u str {
+    url.strip_prefix(base_endpoint)
+        .unwrap_or(url)
+        .trim_start_matches('/')
+        .split(['/', '?'])
+        .next()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_endpoint_group() {
+        let base = "https://pix.example.com/api/v2";
+
+        assert_eq!(endpoint_group(base, "https://pix.example.com/api/v2/cob/abc"), "cob");
+        assert_eq!(
+            endpoint_group(base, "https://pix.example.com/api/v2/pix?inicio=x"),
+            "pix"
+        );
+        assert_eq!(
+            endpoint_group(base, "https://pix.example.com/api/v2/webhook"),
+            "webhook"
+        );
+    }
+
+    #[tokio::test]
+    async fn t_requests_over_quota_wait() {
+        let rate_limiter = RateLimiter::new()
+            .group("cob", Quota::new(2, Duration::from_millis(200)))
+            .default_quota(Quota::per_second(100));
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            rate_limiter.acquire("cob").await;
+        }
+        rate_limiter.acquire("pix").await;
+
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        let metrics = rate_limiter.metrics();
+        assert_eq!(metrics["cob"].requests, 3);
+        assert_eq!(metrics["cob"].throttled, 1);
+        assert!(metrics["cob"].max_wait > Duration::from_millis(90));
+        assert_eq!(metrics["*"].requests, 1);
+        assert_eq!(metrics["*"].throttled, 0);
+    }
+}
@@ -0,0 +1,405 @@
+//! Conciliação entre cobranças imediatas emitidas e Pix recebidos.
+//!
+//! Os Pix recebidos, vindos do webhook ou da listagem de [`crate::pix::PixEndpoint::consultar_recebidos`], são
+//! associados às cobranças pelo `txid`. Cobranças removidas ou concluídas são classificadas pelo seu `status`, e nas
+//! demais o valor recebido, descontadas as devoluções já liquidadas, é comparado ao `valor.original`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use chrono::Utc;
+//! use pix_api_client::cob::CobrancaImediata;
+//! use pix_api_client::reconciliacao::{conciliar, SituacaoConciliacao};
+//! use pix_api_client::webhook::PixInput;
+//!
+//! # fn doc_test(cobrancas: Vec<CobrancaImediata>, recebidos: Vec<PixInput>) {
+//! let relatorio = conciliar(cobrancas, recebidos, Utc::now());
+//!
+//! for item in relatorio.divergencias() {
+//!     println!("{}: {:?}", item.txid, item.situacao);
+//! }
+//! for pix in &relatorio.pix_sem_cobranca {
+//!     println!("Pix {} sem cobrança associada", pix.end_to_end_id);
+//! }
+//! # }
+//! ```
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cob::{CobrancaImediata, CobrancaStatus};
+use crate::webhook::{PixInput, StatusDevolucao};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SituacaoConciliacao {
+    /// Recebido exatamente o valor da cobrança, ou qualquer valor quando o pagador pode alterá-lo.
+    Paga,
+    PagaParcialmente,
+    PagaAMaior,
+    /// Todo o valor recebido foi devolvido ao pagador.
+    Devolvida,
+    AguardandoPagamento,
+    ExpiradaSemPagamento,
+    /// A cobrança tem o status `CONCLUIDA`, mas nenhum Pix foi associado a ela.
+    ConcluidaSemPagamento,
+    /// A cobrança tem o status `REMOVIDA_PELO_USUARIO_RECEBEDOR` ou `REMOVIDA_PELO_PSP`.
+    Removida,
+    /// Outra cobrança informada tem o mesmo `txid`. Os Pix com esse `txid` são associados a todas elas, e precisam
+    /// ser conferidos manualmente.
+    TxidDuplicado,
+    /// O valor da cobrança, de um Pix ou de uma devolução não está no formato `\d+(\.\d{1,2})?`. Os valores do item
+    /// somam somente os demais, e precisam ser conferidos manualmente.
+    ValorInvalido,
+}
+
+/// Resultado da conciliação de uma cobrança. Os valores são representados em centavos.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemConciliacao {
+    pub txid: String,
+    pub situacao: SituacaoConciliacao,
+    pub valor_esperado: i64,
+    pub valor_recebido: i64,
+    /// Soma das devoluções com status `DEVOLVIDO`.
+    pub valor_devolvido: i64,
+    /// `endToEndId` dos Pix associados à cobrança.
+    pub end_to_end_ids: Vec<String>,
+}
+
+impl ItemConciliacao {
+    /// Valor recebido, descontadas as devoluções.
+    pub fn valor_liquido(&self) -> i64 {
+        self.valor_recebido - self.valor_devolvido
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelatorioConciliacao {
+    /// Uma entrada por cobrança conciliada, na ordem em que foram informadas.
+    pub itens: Vec<ItemConciliacao>,
+    /// Pix recebidos sem `txid`, ou cujo `txid` não corresponde a nenhuma cobrança informada.
+    pub pix_sem_cobranca: Vec<PixInput>,
+}
+
+impl RelatorioConciliacao {
+    /// Itens que exigem atenção: pagamentos parciais ou a maior, devoluções, cobranças expiradas ou concluídas sem
+    /// pagamento, cobranças removidas que receberam algum Pix, `txid`s duplicados e valores inválidos.
+    pub fn divergencias(&self) -> impl Iterator<Item = &ItemConciliacao> {
+        self.itens.iter().filter(|item| match item.situacao {
+            SituacaoConciliacao::Paga | SituacaoConciliacao::AguardandoPagamento => item.valor_devolvido > 0,
+            SituacaoConciliacao::Removida => item.valor_recebido > 0,
+            _ => true,
+        })
+    }
+
+    pub fn por_situacao(&self, situacao: SituacaoConciliacao) -> impl Iterator<Item = &ItemConciliacao> {
+        self.itens.iter().filter(move |item| item.situacao == situacao)
+    }
+}
+
+/// Concilia `cobrancas` com os Pix `recebidos`, considerando expiradas as cobranças não pagas cuja expiração é
+/// anterior a `agora`.
+///
+/// Cobranças sem `txid` são ignoradas, e Pix repetidos, identificados pelo `endToEndId`, são considerados uma
+/// única vez. Cobranças que compartilham o mesmo `txid` resultam em [`SituacaoConciliacao::TxidDuplicado`], e as
+/// com algum valor em formato inválido em [`SituacaoConciliacao::ValorInvalido`].
+pub fn conciliar<C, P>(cobrancas: C, recebidos: P, agora: DateTime<Utc>) -> RelatorioConciliacao
+where
+    C: IntoIterator<Item = CobrancaImediata>,
+    P: IntoIterator<Item = PixInput>,
+{
+    let cobrancas: Vec<CobrancaImediata> = cobrancas.into_iter().filter(|c| c.txid.is_some()).collect();
+    let mut txids: HashMap<&str, usize> = HashMap::new();
+    for txid in cobrancas.iter().filter_map(|c| c.txid.as_deref()) {
+        *txids.entry(txid).or_default() += 1;
+    }
+
+    let mut vistos = HashSet::new();
+    let mut por_txid: HashMap<String, Vec<PixInput>> = HashMap::new();
+    let mut pix_sem_cobranca = Vec::new();
+
+    for pix in recebidos {
+        if !vistos.insert(pix.end_to_end_id.clone()) {
+            continue;
+        }
+
+        match pix.txid.as_deref() {
+            Some(txid) if txids.contains_key(txid) => por_txid.entry(txid.to_string()).or_default().push(pix),
+            _ => pix_sem_cobranca.push(pix),
+        }
+    }
+
+    let itens = cobrancas
+        .iter()
+        .map(|cobranca| {
+            let txid = cobranca.txid.clone().unwrap_or_default();
+            let duplicado = txids[txid.as_str()] > 1;
+            let pix = por_txid.get(&txid).cloned().unwrap_or_default();
+            conciliar_cobranca(cobranca, txid, duplicado, pix, agora)
+        })
+        .collect();
+
+    RelatorioConciliacao {
+        itens,
+        pix_sem_cobranca,
+    }
+}
+
+fn conciliar_cobranca(
+    cobranca: &CobrancaImediata,
+    txid: String,
+    duplicado: bool,
+    pix: Vec<PixInput>,
+    agora: DateTime<Utc>,
+) -> ItemConciliacao {
+    let mut valor_invalido = false;
+    let mut centavos_validos = |valor: &str| {
+        let centavos = centavos(valor);
+        valor_invalido |= centavos.is_none();
+        centavos.unwrap_or_default()
+    };
+
+    let valor_esperado = centavos_validos(&cobranca.valor.original);
+    let valor_recebido = pix.iter().map(|pix| centavos_validos(&pix.valor)).sum();
+    let valor_devolvido = pix
+        .iter()
+        .flat_map(|pix| pix.devolucoes.iter().flatten())
+        .filter(|devolucao| devolucao.status == StatusDevolucao::DEVOLVIDO)
+        .map(|devolucao| centavos_validos(&devolucao.valor))
+        .sum();
+
+    let valor_liquido: i64 = valor_recebido - valor_devolvido;
+    let valor_alteravel = cobranca.valor.permite_alteracao == Some(1);
+
+    let situacao = match cobranca.status {
+        _ if duplicado => SituacaoConciliacao::TxidDuplicado,
+        Some(CobrancaStatus::REMOVIDA_PELO_USUARIO_RECEBEDOR) | Some(CobrancaStatus::REMOVIDA_PELO_PSP) => {
+            SituacaoConciliacao::Removida
+        }
+        _ if valor_invalido => SituacaoConciliacao::ValorInvalido,
+        Some(CobrancaStatus::CONCLUIDA) if pix.is_empty() => SituacaoConciliacao::ConcluidaSemPagamento,
+        _ if valor_recebido == 0 => match expiracao(cobranca) {
+            Some(expiracao) if expiracao < agora => SituacaoConciliacao::ExpiradaSemPagamento,
+            _ => SituacaoConciliacao::AguardandoPagamento,
+        },
+        _ => situacao_por_valor(valor_liquido, valor_esperado, valor_alteravel),
+    };
+
+    ItemConciliacao {
+        txid,
+        situacao,
+        valor_esperado,
+        valor_recebido,
+        valor_devolvido,
+        end_to_end_ids: pix.into_iter().map(|pix| pix.end_to_end_id).collect(),
+    }
+}
+
+/// Situação de uma cobrança que recebeu algum valor.
+fn situacao_por_valor(valor_liquido: i64, valor_esperado: i64, valor_alteravel: bool) -> SituacaoConciliacao {
+    if valor_liquido <= 0 {
+        SituacaoConciliacao::Devolvida
+    } else if valor_alteravel || valor_liquido == valor_esperado {
+        SituacaoConciliacao::Paga
+    } else if valor_liquido < valor_esperado {
+        SituacaoConciliacao::PagaParcialmente
+    } else {
+        SituacaoConciliacao::PagaAMaior
+    }
+}
+
+fn expiracao(cobranca: &CobrancaImediata) -> Option<DateTime<Utc>> {
+    let criacao = DateTime::parse_from_rfc3339(cobranca.calendario.criacao.as_deref()?).ok()?;
+    Some(criacao.with_timezone(&Utc) + Duration::seconds(cobranca.calendario.expiracao))
+}
+
+/// Converte um valor no formato `\d+(\.\d{1,2})?` para centavos. Sinais, espaços e expoentes não são aceitos.
+fn centavos(valor: &str) -> Option<i64> {
+    let (reais, centavos) = valor.split_once('.').unwrap_or((valor, "0"));
+    let digitos = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if !digitos(reais) || !digitos(centavos) || centavos.len() > 2 {
+        return None;
+    }
+
+    let centavos: i64 = format!("{:0<2}", centavos).parse().ok()?;
+    reais.parse::<i64>().ok()?.checked_mul(100)?.checked_add(centavos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cob::Devedor;
+
+    fn cobranca(txid: &str, valor: f64) -> CobrancaImediata {
+        let mut cobranca = CobrancaImediata::new(
+            valor,
+            "7d9f0335-8dcc-4054-9bf9-0dbd61d36906".to_string(),
+            Devedor::new_pessoa_fisica("12345678909".to_string(), "Francisco da Silva".to_string()),
+        );
+        cobranca.txid = Some(txid.to_string());
+        cobranca.calendario.criacao = Some("2020-09-09T20:15:00.358Z".to_string());
+        cobranca
+    }
+
+    fn pix(end_to_end_id: &str, txid: &str, valor: &str, devolucoes: &str) -> PixInput {
+        serde_json::from_str(&format!(
+            r#"{{
+                "endToEndId": "{}",
+                "txid": "{}",
+                "chave": "7d9f0335-8dcc-4054-9bf9-0dbd61d36906",
+                "valor": "{}",
+                "horario": "2020-09-09T20:20:00.000Z",
+                "devolucoes": [{}]
+            }}"#,
+            end_to_end_id, txid, valor, devolucoes
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn t_centavos() {
+        assert_eq!(centavos("123.45"), Some(12345));
+        assert_eq!(centavos("0.5"), Some(50));
+        assert_eq!(centavos("10"), Some(1000));
+        assert_eq!(centavos("1.234"), None);
+        assert_eq!(centavos("abc"), None);
+
+        for invalido in ["-0.50", "+5.00", "10.", ".50", " 1.00", "1.-5", "1e3", ""] {
+            assert_eq!(centavos(invalido), None, "{:?}", invalido);
+        }
+    }
+
+    #[test]
+    fn t_conciliar_valor_invalido() {
+        let mut cobranca_invalida = cobranca("cobranca", 100.0);
+        cobranca_invalida.valor.original = "100,00".to_string();
+
+        let relatorio = conciliar(
+            vec![cobranca_invalida, cobranca("pix", 100.0), cobranca("negativo", 100.0)],
+            vec![
+                pix("E1", "cobranca", "100.00", ""),
+                pix("E2", "pix", "+100.00", ""),
+                pix("E3", "negativo", "100.00", ""),
+                pix("E4", "negativo", "-0.50", ""),
+            ],
+            Utc::now(),
+        );
+
+        assert!(relatorio
+            .itens
+            .iter()
+            .all(|item| item.situacao == SituacaoConciliacao::ValorInvalido));
+        assert_eq!(relatorio.itens[2].valor_recebido, 10000);
+        assert_eq!(relatorio.divergencias().count(), 3);
+    }
+
+    #[test]
+    fn t_conciliar_por_status() {
+        let status = |txid: &str, status: CobrancaStatus| {
+            let mut cobranca = cobranca(txid, 100.0);
+            cobranca.status = Some(status);
+            cobranca
+        };
+
+        let cobrancas = vec![
+            status("removida", CobrancaStatus::REMOVIDA_PELO_USUARIO_RECEBEDOR),
+            status("removida_psp", CobrancaStatus::REMOVIDA_PELO_PSP),
+            status("concluida", CobrancaStatus::CONCLUIDA),
+            status("concluida_paga", CobrancaStatus::CONCLUIDA),
+        ];
+        let recebidos = vec![
+            pix("E1", "removida_psp", "100.00", ""),
+            pix("E2", "concluida_paga", "100.00", ""),
+        ];
+
+        let relatorio = conciliar(cobrancas, recebidos, Utc::now());
+        let situacoes: Vec<_> = relatorio.itens.iter().map(|item| item.situacao).collect();
+
+        assert_eq!(
+            situacoes,
+            vec![
+                SituacaoConciliacao::Removida,
+                SituacaoConciliacao::Removida,
+                SituacaoConciliacao::ConcluidaSemPagamento,
+                SituacaoConciliacao::Paga,
+            ]
+        );
+        let divergencias: Vec<_> = relatorio.divergencias().map(|item| item.txid.as_str()).collect();
+        assert_eq!(divergencias, vec!["removida_psp", "concluida"]);
+    }
+
+    #[test]
+    fn t_conciliar_txid_duplicado() {
+        let relatorio = conciliar(
+            vec![
+                cobranca("duplicado", 100.0),
+                cobranca("unico", 100.0),
+                cobranca("duplicado", 50.0),
+            ],
+            vec![pix("E1", "duplicado", "100.00", ""), pix("E2", "unico", "100.00", "")],
+            Utc::now(),
+        );
+        let situacoes: Vec<_> = relatorio.itens.iter().map(|item| item.situacao).collect();
+
+        assert_eq!(
+            situacoes,
+            vec![
+                SituacaoConciliacao::TxidDuplicado,
+                SituacaoConciliacao::Paga,
+                SituacaoConciliacao::TxidDuplicado,
+            ]
+        );
+        assert_eq!(relatorio.itens[0].end_to_end_ids, vec!["E1"]);
+        assert_eq!(relatorio.itens[2].end_to_end_ids, vec!["E1"]);
+        assert_eq!(relatorio.divergencias().count(), 2);
+        assert!(relatorio.pix_sem_cobranca.is_empty());
+    }
+
+    #[test]
+    fn t_conciliar() {
+        let devolucao = r#"{
+            "id": "123456",
+            "rtrId": "D12345678202009091000abcde123456",
+            "valor": "50.00",
+            "horario": { "solicitacao": "2020-09-10T15:25:59.411Z" },
+            "status": "DEVOLVIDO"
+        }"#;
+
+        let cobrancas = vec![
+            cobranca("paga", 100.0),
+            cobranca("parcial", 100.0),
+            cobranca("amaior", 100.0),
+            cobranca("devolvida", 50.0),
+            cobranca("expirada", 100.0),
+        ];
+        let recebidos = vec![
+            pix("E1", "paga", "100.00", ""),
+            pix("E1", "paga", "100.00", ""),
+            pix("E2", "parcial", "60.00", ""),
+            pix("E3", "amaior", "150.00", ""),
+            pix("E4", "devolvida", "50.00", devolucao),
+            pix("E5", "desconhecido", "10.00", ""),
+        ];
+        let agora = DateTime::parse_from_rfc3339("2020-09-10T20:15:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let relatorio = conciliar(cobrancas, recebidos, agora);
+        let situacoes: Vec<_> = relatorio.itens.iter().map(|item| item.situacao).collect();
+
+        assert_eq!(
+            situacoes,
+            vec![
+                SituacaoConciliacao::Paga,
+                SituacaoConciliacao::PagaParcialmente,
+                SituacaoConciliacao::PagaAMaior,
+                SituacaoConciliacao::Devolvida,
+                SituacaoConciliacao::ExpiradaSemPagamento,
+            ]
+        );
+        assert_eq!(relatorio.itens[0].end_to_end_ids, vec!["E1"]);
+        assert_eq!(relatorio.itens[3].valor_liquido(), 0);
+        assert_eq!(relatorio.divergencias().count(), 4);
+        assert_eq!(relatorio.pix_sem_cobranca[0].end_to_end_id, "E5");
+    }
+}
@@ -0,0 +1,425 @@
+//! Retries of requests that failed due to transient PSP failures.
+//!
+//! A request is retried when the connection fails, times out, or when the PSP answers with `429`, `502`, `503` or
+//! `504`. By default, only `GET`, and `PUT` to routes identified by the client, such as `/cob/{txid}`, are retried,
+//! since a `POST` that timed out may have already created a charge on the PSP. See [`RetryMethods`] to retry other
+//! methods.
+//!
+//! # Example: Retry a single `POST`
+//!
+//! ```no_run
+//! use pix_api_client::cob::{CobrancaImediata, Devedor};
+//! use pix_api_client::retry::{RetryMethods, RetryPolicy};
+//! use pix_api_client::{Executor, PixClient};
+//!
+//! # async fn doc_test(pix_client: PixClient) -> Result<(), anyhow::Error> {
+//! let devedor = Devedor::new_pessoa_fisica("00000000000".to_string(), "Fulano de tal".to_string());
+//! let payload = CobrancaImediata::new(10.25, "my-key".to_string(), devedor);
+//!
+//! let response = pix_client
+//!     .cob()
+//!     .criar_cobranca_imediata(payload)
+//!     .retry_policy(RetryPolicy::default().methods(RetryMethods::All))
+//!     .execute()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+use std::time::{Duration, SystemTime};
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Method, Request, StatusCode};
+
+use crate::errors::{ApiResult, PixError};
+use crate::{telemetry, PixClient, RawResponse};
+
+/// How many times, and how often, a request is attempted.
+///
+/// The delay before the `n`-th retry is `base_delay * 2^(n - 1)`, capped at `max_delay`, and a random amount of it
+/// is used when `jitter` is enabled. A `Retry-After` header sent by the PSP takes precedence over the backoff, and is
+/// also capped at `max_delay`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    methods: RetryMethods,
+}
+
+/// Which requests a [`RetryPolicy`] retries.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RetryMethods {
+    /// `GET`, and `PUT` to routes ending with an identifier chosen by the client, which makes the creation
+    /// idempotent: `/cob/{txid}`, `/cobv/{txid}`, `/lotecobv/{id}`, `/pix/{e2eid}/devolucao/{id}` and
+    /// `/webhook/{chave}`.
+    GetAndPutWithId,
+    /// Every method idempotent by HTTP semantics: `GET`, `HEAD`, `PUT`, `DELETE` and `OPTIONS`, including a `PUT`
+    /// or `DELETE` that the PSP may not handle idempotently.
+    Idempotent,
+    /// Any method, including `POST` and `PATCH`.
+    All,
+}
+
+impl Default for RetryPolicy {
+    /// Up to 3 attempts of [`RetryMethods::GetAndPutWithId`] requests, starting with a 200ms backoff, never waiting
+    /// more than 10 seconds. Other idempotent methods must be enabled with
+    /// [`methods(RetryMethods::Idempotent)`](RetryPolicy::methods).
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+            methods: RetryMethods::GetAndPutWithId,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Every request is attempted a single time.
+    pub fn none() -> RetryPolicy {
+        Self::default().max_attempts(1)
+    }
+
+    /// Total number of attempts, including the first one.
+    pub fn max_attempts(mut self, max_attempts: u32) -> RetryPolicy {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn base_delay(mut self, base_delay: Duration) -> RetryPolicy {
+        self.base_delay = base_delay;
+        self
+    }
+
+    pub fn max_delay(mut self, max_delay: Duration) -> RetryPolicy {
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> RetryPolicy {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Defaults to [`RetryMethods::GetAndPutWithId`].
+    pub fn methods(mut self, methods: RetryMethods) -> RetryPolicy {
+        self.methods = methods;
+        self
+    }
+
+    fn allows(&self, base_endpoint: &str, request: &Request) -> bool {
+        let method = request.method();
+        let allowed = match self.methods {
+            RetryMethods::GetAndPutWithId => {
+                *method == Method::GET
+                    || (*method == Method::PUT
+                        && telemetry::endpoint_template(base_endpoint, request.url().as_str()).ends_with('}'))
+            }
+            RetryMethods::Idempotent => matches!(
+                *method,
+                Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+            ),
+            RetryMethods::All => true,
+        };
+
+        self.max_attempts > 1 && allowed
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .checked_mul(2u32.saturating_pow(attempt - 1))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+
+        if self.jitter {
+            exponential.mul_f64(fastrand::f64())
+        } else {
+            exponential
+        }
+    }
+
+    /// How long to wait before retrying, or `None` when `result` must be returned as is.
+    fn delay(&self, result: &ApiResult<RawResponse>, attempt: u32) -> Option<Duration> {
+        match result {
+            Ok(response) if is_transient(response.status) => match retry_after(&response.headers) {
+                Some(delay) => Some(delay.min(self.max_delay)),
+                None => Some(self.backoff(attempt)),
+            },
+            Err(PixError::NetworkError(e)) if e.is_connect() || e.is_timeout() || e.is_request() => {
+                Some(self.backoff(attempt))
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) async fn send(
+        &self,
+        client: &PixClient,
+        mut request: Request,
+        managed_authorization: bool,
+    ) -> ApiResult<RawResponse> {
+        let allowed = self.allows(&client.base_endpoint, &request);
+        let mut attempt = 1;
+
+        loop {
+            let retry = if allowed && attempt < self.max_attempts {
+                request.try_clone()
+            } else {
+                None
+            };
+
+            let result = client.dispatch(request, managed_authorization).await;
+
+            match (retry, self.delay(&result, attempt)) {
+                (Some(retry), Some(delay)) => {
+                    tracing::warn!(attempt, ?delay, "transient failure, retrying");
+                    tokio::time::sleep(delay).await;
+
+                    request = retry;
+                    attempt += 1;
+                }
+                _ => return result,
+            }
+        }
+    }
+}
+
+fn is_transient(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// `Retry-After` is either an amount of seconds or an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    match value.parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => {
+            let date = httpdate::parse_http_date(value).ok()?;
+            Some(date.duration_since(SystemTime::now()).unwrap_or_default())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::cob::{CobrancaImediata, Devedor};
+    use crate::lotecobv::LoteCobvPayload;
+    use crate::pix::DevolucaoPayload;
+    use crate::{CobTxid, Executor};
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy::default()
+            .base_delay(Duration::from_millis(10))
+            .jitter(false)
+    }
+
+    fn mock_client(server: &MockServer, retry_policy: RetryPolicy) -> PixClient {
+        PixClient::from_client(reqwest::Client::new(), &server.uri(), HeaderMap::new()).with_retry_policy(retry_policy)
+    }
+
+    fn devolucao_response() -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "id": "123456",
+            "rtrId": "D12345678202009091000abcde123456",
+            "valor": "7.89",
+            "horario": { "solicitacao": "2020-09-11T15:25:59.411Z" },
+            "status": "EM_PROCESSAMENTO"
+        }))
+    }
+
+    #[tokio::test]
+    async fn t_transient_failures_are_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/cob/7978c0c97ea847e78e8849634473c1f1"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/cob/7978c0c97ea847e78e8849634473c1f1"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "calendario": { "criacao": "2020-09-09T20:15:00.358Z", "expiracao": 3600 },
+                "txid": "7978c0c97ea847e78e8849634473c1f1",
+                "devedor": { "cpf": "12345678909", "nome": "Francisco da Silva" },
+                "valor": { "original": "10.25" },
+                "chave": "my-key"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, fast_policy());
+        let devedor = Devedor::new_pessoa_fisica("12345678909".to_string(), "Francisco da Silva".to_string());
+        let cobranca = client
+            .cob()
+            .criar_cobranca_txid(
+                &CobTxid::new("7978c0c97ea847e78e8849634473c1f1").unwrap(),
+                CobrancaImediata::new(10.25, "my-key".to_string(), devedor),
+            )
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(cobranca.txid.as_deref(), Some("7978c0c97ea847e78e8849634473c1f1"));
+    }
+
+    #[tokio::test]
+    async fn t_gives_up_after_max_attempts() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/pix/E12345678202009091221abcdef12345/devolucao/123456"))
+            .respond_with(ResponseTemplate::new(502))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, fast_policy());
+        let result = client
+            .pix()
+            .consultar_devolucao("E12345678202009091221abcdef12345".to_string(), "123456".to_string())
+            .execute()
+            .await;
+
+        assert!(matches!(result, Err(PixError::UnexpectedStatus(_))));
+    }
+
+    #[tokio::test]
+    async fn t_post_is_not_retried_by_default() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/cob"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, fast_policy());
+        let devedor = Devedor::new_pessoa_fisica("00000000000".to_string(), "Fulano de tal".to_string());
+        let result = client
+            .cob()
+            .criar_cobranca_imediata(CobrancaImediata::new(10.25, "my-key".to_string(), devedor))
+            .execute()
+            .await;
+
+        assert!(matches!(result, Err(PixError::ServiceUnavailable(_))));
+    }
+
+    #[tokio::test]
+    async fn t_put_with_id_is_retried_by_default() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/lotecobv/42"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/lotecobv/42"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, fast_policy());
+        client
+            .lotecobv()
+            .criar_lote(42, LoteCobvPayload::new("Mensalidades".to_string(), vec![]))
+            .execute()
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn t_retry_after_is_capped_at_max_delay() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/pix/E12345678202009091221abcdef12345/devolucao/123456"))
+            .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "120"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/pix/E12345678202009091221abcdef12345/devolucao/123456"))
+            .respond_with(devolucao_response())
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, fast_policy().max_delay(Duration::from_millis(50)));
+        let start = Instant::now();
+        client
+            .pix()
+            .consultar_devolucao("E12345678202009091221abcdef12345".to_string(), "123456".to_string())
+            .execute()
+            .await
+            .unwrap();
+
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn t_per_request_policy_and_retry_after() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/pix/E12345678202009091221abcdef12345/devolucao/123456"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/pix/E12345678202009091221abcdef12345/devolucao/123456"))
+            .respond_with(devolucao_response())
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, RetryPolicy::none());
+        let start = Instant::now();
+        client
+            .pix()
+            .solicitar_devolucao(
+                "E12345678202009091221abcdef12345".to_string(),
+                "123456".to_string(),
+                DevolucaoPayload::new(7.89),
+            )
+            .retry_policy(fast_policy().methods(RetryMethods::Idempotent))
+            .execute()
+            .await
+            .unwrap();
+
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn t_retry_after_http_date() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("Sun, 06 Nov 1994 08:49:37 GMT"));
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+    }
+}
@@ -0,0 +1,147 @@
+//! Tracing spans and redaction of personal data from logged requests.
+//!
+//! Every executed request is wrapped in a `pix_request` span, recording its method, endpoint template, txid, status,
+//! latency and correlation id. Bodies and headers are only logged at `DEBUG`, with `cpf`, `cnpj`, `nome`, tokens and
+//! the `Authorization` header masked. Failed requests are logged at `WARN` with only their status, problem `type` and
+//! `title`, and correlation id.
+use reqwest::header::{HeaderMap, AUTHORIZATION};
+use serde_json::Value;
+
+const MASK: &str = "***";
+
+const SENSITIVE_FIELDS: &[&str] = &["cpf", "cnpj", "nome", "nomeFantasia", "access_token"];
+
+/// Headers PSP's use to identify a request on their side.
+const CORRELATION_HEADERS: &[&str] = &["x-correlation-id", "x-request-id", "x-trace-id"];
+
+/// Path of `url` relative to `base_endpoint`, with its identifiers replaced by placeholders, such as
+/// `/pix/{e2eid}/devolucao/{id}`.
+pub(crate) fn endpoint_template(base_endpoint: &str, url: &str) -> String {
+    let mut template = String::new();
+    let mut placeholder = None;
+
+    for segment in relative_path(base_endpoint, url).split('/').filter(|s| !s.is_empty()) {
+        template.push('/');
+        match placeholder.take() {
+            Some(placeholder) => template.push_str(placeholder),
+            None => {
+                template.push_str(segment);
+                placeholder = match segment {
+                    "cob" | "cobv" => Some("{txid}"),
+                    "loc" | "lotecobv" | "devolucao" => Some("{id}"),
+                    "pix" => Some("{e2eid}"),
+                    "webhook" => Some("{chave}"),
+                    _ => None,
+                };
+            }
+        }
+    }
+    template
+}
+
+/// The txid of requests to `/cob/{txid}` and `/cobv/{txid}`.
+pub(crate) fn txid<'u>(base_endpoint: &str, url: &'u str) -> Option<&'u str> {
+    let mut segments = relative_path(base_endpoint, url).split('/').filter(|s| !s.is_empty());
+
+    match segments.next() {
+        Some("cob") | Some("cobv") => segments.next(),
+        _ => None,
+    }
+}
+
+pub(crate) fn correlation_id(headers: &HeaderMap) -> Option<&str> {
+    CORRELATION_HEADERS
+        .iter()
+        .find_map(|name| headers.get(*name))
+        .and_then(|value| value.to_str().ok())
+}
+
+/// Path of `url` after `base_endpoint`, without the query string.
+fn relative_path<'u>(base_endpoint: &str, url: &'u str) -> &'u str {
+    let path = url.strip_prefix(base_endpoint).unwrap_or(url);
+    path.split('?').next().unwrap_or_default()
+}
+
+/// Masks personal data of a JSON body. Any other body is kept as is.
+pub(crate) fn redact_body(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(mut json) => {
+            redact_json(&mut json);
+            json.to_string()
+        }
+        Err(_) => body.to_string(),
+    }
+}
+
+fn redact_json(json: &mut Value) {
+    match json {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if SENSITIVE_FIELDS.contains(&key.as_str()) && !value.is_null() {
+                    *value = Value::String(MASK.to_string());
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Headers in a printable form, with `Authorization` and any value marked as sensitive masked.
+pub(crate) fn redact_headers(headers: &HeaderMap) -> Vec<(&str, &str)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if name == AUTHORIZATION || value.is_sensitive() {
+                MASK
+            } else {
+                value.to_str().unwrap_or(MASK)
+            };
+            (name.as_str(), value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    const BASE: &str = "https://pix.example.com/api/v2";
+
+    #[test]
+    fn t_endpoint_template() {
+        let template = |path: &str| endpoint_template(BASE, &format!("{}{}", BASE, path));
+
+        assert_eq!(template("/cob/abc123?revisao=1"), "/cob/{txid}");
+        assert_eq!(template("/cob?cpf=12345678909"), "/cob");
+        assert_eq!(template("/loc/7768/txid"), "/loc/{id}/txid");
+        assert_eq!(
+            template("/pix/E12345678202009091221abcdef12345/devolucao/123456"),
+            "/pix/{e2eid}/devolucao/{id}"
+        );
+        assert_eq!(template("/oauth/token"), "/oauth/token");
+        assert_eq!(txid(BASE, &format!("{}/cobv/abc123", BASE)), Some("abc123"));
+    }
+
+    #[test]
+    fn t_redact() {
+        let body = r#"{"devedor":{"cpf":"12345678909","nome":"Francisco da Silva"},"valor":{"original":"123.45"},"infoAdicionais":[{"nome":"Campo 1","valor":"x"}]}"#;
+
+        assert_eq!(
+            redact_body(body),
+            r#"{"devedor":{"cpf":"***","nome":"***"},"infoAdicionais":[{"nome":"***","valor":"x"}],"valor":{"original":"123.45"}}"#
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer my-token"));
+        headers.insert("x-api-key", HeaderValue::from_static("my-api-key"));
+        assert_eq!(
+            redact_headers(&headers),
+            vec![("authorization", "***"), ("x-api-key", "my-api-key")]
+        );
+    }
+}
@@ -1,6 +1,11 @@
+use std::future::Future;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
 
+use crate::paginacao::{PaginacaoParams, Paginado, Parametros};
 use crate::{ApiRequest, PixClient};
 
 pub struct WebhookEndpoint<'a> {
@@ -20,13 +25,72 @@ impl WebHookPayload {
 }
 
 impl PixClient {
-    pub fn webhook(&self) -> WebhookEndpoint {
+    pub fn webhook(&self) -> WebhookEndpoint<'_> {
         WebhookEndpoint { inner: self }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct WebHookResponse {}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebHookResponse {
+    #[serde(rename = "webhookUrl")]
+    pub webhook_url: String,
+    pub chave: String,
+    pub criacao: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebHooks {
+    pub parametros: Parametros,
+    pub webhooks: Vec<WebHookResponse>,
+}
+
+impl Paginado for WebHooks {
+    type Item = WebHookResponse;
+
+    fn parametros(&self) -> &Parametros {
+        &self.parametros
+    }
+
+    fn into_items(self) -> Vec<WebHookResponse> {
+        self.webhooks
+    }
+}
+
+/// Filtros aceitos pela listagem de webhooks.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebHookFiltro {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inicio: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fim: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    pub paginacao: PaginacaoParams,
+}
+
+impl WebHookFiltro {
+    /// Filtra os webhooks criados no período.
+    pub fn new(inicio: DateTime<Utc>, fim: DateTime<Utc>) -> Self {
+        Self {
+            inicio: Some(inicio),
+            fim: Some(fim),
+            paginacao: Default::default(),
+        }
+    }
+
+    /// Sem filtros de data, lista todos os webhooks cadastrados.
+    pub fn todos() -> Self {
+        Self {
+            inicio: None,
+            fim: None,
+            paginacao: Default::default(),
+        }
+    }
+
+    pub fn paginacao(mut self, paginacao: PaginacaoParams) -> Self {
+        self.paginacao = paginacao;
+        self
+    }
+}
 
 /// Base response object used by the `WebHook` for any transaction.
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -34,6 +98,37 @@ pub struct WebHookCallbackResponse {
     pub pix: Vec<PixInput>,
 }
 
+pub type HandlerError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Processes each Pix received through the webhook.
+///
+/// Used by [`crate::deduplication::Deduplicado`], and by the receiver of the `webhook-server` feature.
+///
+/// Implemented by any `async` closure taking a [`PixInput`].
+#[async_trait]
+pub trait WebHookHandler: Send + Sync + 'static {
+    async fn handle(&self, pix: PixInput) -> Result<(), HandlerError>;
+
+    /// Processes a status change of a devolução of the Pix `end_to_end_id`. Only invoked by
+    /// [`crate::deduplication::Deduplicado`], which delivers devoluções exclusively through this method. Ignored by
+    /// default.
+    async fn handle_devolucao(&self, end_to_end_id: &str, devolucao: Devolucoes) -> Result<(), HandlerError> {
+        let _ = (end_to_end_id, devolucao);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<F, Fut> WebHookHandler for F
+where
+    F: Fn(PixInput) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), HandlerError>> + Send,
+{
+    async fn handle(&self, pix: PixInput) -> Result<(), HandlerError> {
+        self(pix).await
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PixInput {
     #[serde(rename = "endToEndId")]
@@ -60,37 +155,165 @@ pub struct Devolucoes {
     #[serde(rename = "rtrId")]
     pub rtr_id: String,
     pub valor: String,
+    /// Quando ausente, deve ser interpretada como `ORIGINAL`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub natureza: Option<NaturezaDevolucao>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub descricao: Option<String>,
     pub horario: Horario,
-    pub status: String,
+    pub status: StatusDevolucao,
+    /// Motivo atribuído à devolução, preenchido principalmente quando o `status` é `NAO_REALIZADO`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub motivo: Option<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[allow(non_camel_case_types)]
+pub enum NaturezaDevolucao {
+    /// Devolução de um Pix comum.
+    ORIGINAL,
+    /// Devolução de um Pix Troco ou Pix Saque.
+    RETIRADA,
+    /// Devolução solicitada pelo Mecanismo Especial de Devolução, por falha operacional.
+    MED_OPERACIONAL,
+    /// Devolução solicitada pelo Mecanismo Especial de Devolução, por fundada suspeita de fraude.
+    MED_FRAUDE,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[allow(non_camel_case_types)]
+pub enum StatusDevolucao {
+    EM_PROCESSAMENTO,
+    DEVOLVIDO,
+    NAO_REALIZADO,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Horario {
     pub solicitacao: String,
+    /// Presente somente após a devolução ser liquidada.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub liquidacao: Option<String>,
 }
 
 impl<'a> WebhookEndpoint<'a> {
-    pub fn criar_por_chave(&self, chave_pix: String, webhook_url: String) -> ApiRequest<WebHookResponse> {
-        let endpoint = format!("{}/webhook/{}", &*self.inner.base_endpoint, chave_pix);
+    /// Configura o webhook que recebe as notificações de Pix recebidos na `chave_pix`. O PSP normalmente responde sem
+    /// corpo, e qualquer corpo retornado é ignorado.
+    pub fn criar_por_chave(&self, chave_pix: String, webhook_url: String) -> ApiRequest<'a, ()> {
+        let endpoint = format!("{}/webhook/{}", self.inner.base_endpoint, chave_pix);
         let payload = WebHookPayload::new(webhook_url);
         self.inner.request_with_headers(Method::PUT, &endpoint, payload)
     }
 
-    pub fn consultar_por_chave(&self, chave_pix: String, webhook_url: String) -> ApiRequest<WebHookResponse> {
-        let endpoint = format!("{}/webhook/{}", &*self.inner.base_endpoint, chave_pix);
-        let payload = WebHookPayload::new(webhook_url);
-        self.inner.request_with_headers(Method::GET, &endpoint, payload)
+    pub fn consultar_por_chave(&self, chave_pix: String) -> ApiRequest<'a, WebHookResponse> {
+        let endpoint = format!("{}/webhook/{}", self.inner.base_endpoint, chave_pix);
+        self.inner.request_with_query(Method::GET, &endpoint, None::<()>)
     }
-    pub fn cancelar_por_chave(&self, chave_pix: String, webhook_url: String) -> ApiRequest<WebHookResponse> {
-        let endpoint = format!("{}/webhook/{}", &*self.inner.base_endpoint, chave_pix);
-        let payload = WebHookPayload::new(webhook_url);
-        self.inner.request_with_headers(Method::DELETE, &endpoint, payload)
+
+    /// Remove o webhook da `chave_pix`. Assim como em [`WebhookEndpoint::criar_por_chave`], o corpo da resposta é
+    /// ignorado.
+    pub fn cancelar_por_chave(&self, chave_pix: String) -> ApiRequest<'a, ()> {
+        let endpoint = format!("{}/webhook/{}", self.inner.base_endpoint, chave_pix);
+        self.inner.request_with_query(Method::DELETE, &endpoint, None::<()>)
+    }
+
+    /// Lista os webhooks cadastrados.
+    pub fn consultar_todos(&self, filtro: WebHookFiltro) -> ApiRequest<'a, WebHooks> {
+        let endpoint = format!("{}/webhook", self.inner.base_endpoint);
+        self.inner.request_with_query(Method::GET, &endpoint, Some(filtro))
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+    use super::*;
+    use crate::header::HeaderMap;
+    use crate::Executor;
+
+    const CHAVE: &str = "40a0932d-1918-4eee-845d-35a2da1690dc";
+
+    #[tokio::test]
+    async fn t_webhook_por_chave() {
+        let server = MockServer::start().await;
+        let endpoint = format!("/webhook/{}", CHAVE);
+        Mock::given(method("PUT"))
+            .and(path(&*endpoint))
+            .and(body_json(
+                json!({ "webhookUrl": "https://pix.example.com/api/webhook/" }),
+            ))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(&*endpoint))
+            .and(|request: &Request| request.body.is_empty())
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "webhookUrl": "https://pix.example.com/api/webhook/",
+                "chave": CHAVE,
+                "criacao": "2020-11-11T10:15:00.358Z"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(&*endpoint))
+            .and(|request: &Request| request.body.is_empty())
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = PixClient::from_client(reqwest::Client::new(), &server.uri(), HeaderMap::new());
+        let webhook = client.webhook();
+
+        webhook
+            .criar_por_chave(CHAVE.to_string(), "https://pix.example.com/api/webhook/".to_string())
+            .execute()
+            .await
+            .unwrap();
+
+        let response = webhook.consultar_por_chave(CHAVE.to_string()).execute().await.unwrap();
+        assert_eq!(response.chave, CHAVE);
+        assert_eq!(response.webhook_url, "https://pix.example.com/api/webhook/");
+
+        webhook.cancelar_por_chave(CHAVE.to_string()).execute().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn t_webhook_com_corpo_na_resposta() {
+        let server = MockServer::start().await;
+        let endpoint = format!("/webhook/{}", CHAVE);
+        let corpo = json!({
+            "webhookUrl": "https://pix.example.com/api/webhook/",
+            "chave": CHAVE,
+            "criacao": "2020-11-11T10:15:00.358Z"
+        });
+        Mock::given(method("PUT"))
+            .and(path(&*endpoint))
+            .respond_with(ResponseTemplate::new(201).set_body_json(&corpo))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(&*endpoint))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&corpo))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = PixClient::from_client(reqwest::Client::new(), &server.uri(), HeaderMap::new());
+        let webhook = client.webhook();
 
-    /// Criar uma cobrança imediata.
-    /// Diferente de `criar_cobranca_imediata`, o `txid` é definido pelo PSP.
-    pub fn consultar_todos(&self) -> ApiRequest<WebHookResponse> {
-        let endpoint = format!("{}/webhook", &*self.inner.base_endpoint);
-        self.inner.request_with_headers(Method::GET, &endpoint, None::<&str>)
+        webhook
+            .criar_por_chave(CHAVE.to_string(), "https://pix.example.com/api/webhook/".to_string())
+            .execute()
+            .await
+            .unwrap();
+        webhook.cancelar_por_chave(CHAVE.to_string()).execute().await.unwrap();
     }
 }
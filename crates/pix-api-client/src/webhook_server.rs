@@ -0,0 +1,304 @@
+//! Receiver of the callbacks sent by the PSP to the url registered with [`crate::webhook::WebhookEndpoint`].
+//!
+//! The PSP delivers every received Pix with a `POST {webhookUrl}/pix`, authenticating itself with a client
+//! certificate. [`WebHookServer`] requires this certificate to be signed by the PSP's CA, and dispatches each
+//! [`PixInput`](crate::webhook::PixInput) of the callback to a [`WebHookHandler`].
+//!
+//! The callback is answered with `200` only when every `PixInput` was handled successfully, otherwise with `500`, so
+//! the PSP delivers it again later. Handlers must then be idempotent.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use std::fs;
+//! use pix_api_client::webhook::PixInput;
+//! use pix_api_client::webhook_server::{HandlerError, WebHookServer};
+//! use tokio::net::TcpListener;
+//!
+//! # async fn doc_test() -> Result<(), anyhow::Error> {
+//! let handler = |pix: PixInput| async move {
+//!     println!("Pix {} received, valor {}", pix.end_to_end_id, pix.valor);
+//!     Ok::<_, HandlerError>(())
+//! };
+//!
+//! let server = WebHookServer::new(
+//!     handler,
+//!     &fs::read("my_server_cert.pem")?,
+//!     &fs::read("my_server_key.pem")?,
+//!     &fs::read("psp_ca.pem")?,
+//! )?;
+//!
+//! server.serve(TcpListener::bind("0.0.0.0:8443").await?).await?;
+//! # Ok(())
+//! # }
+//! ```
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::{Json, Router};
+use hyper::server::conn::Http;
+use thiserror::Error;
+use tokio::net::TcpListener;
+use tokio_rustls::rustls::server::AllowAnyAuthenticatedClient;
+use tokio_rustls::rustls::{self, Certificate, PrivateKey, RootCertStore, ServerConfig};
+use tokio_rustls::TlsAcceptor;
+
+use crate::webhook::WebHookCallbackResponse;
+pub use crate::webhook::{HandlerError, WebHookHandler};
+
+#[derive(Debug, Error)]
+pub enum WebHookServerError {
+    #[error("Invalid PEM: {0}")]
+    InvalidPem(String),
+    #[error("Invalid TLS configuration: {0}")]
+    Tls(#[from] rustls::Error),
+}
+
+/// Routes `POST /pix` to `handler`, without any TLS. Useful for serving the webhook behind a reverse proxy that
+/// already terminates mutual TLS, or nesting it in an existing axum application.
+pub fn router<H: WebHookHandler>(handler: H) -> Router {
+    Router::new()
+        .route("/pix", post(receive::<H>))
+        .with_state(Arc::new(handler))
+}
+
+async fn receive<H: WebHookHandler>(
+    State(handler): State<Arc<H>>,
+    Json(callback): Json<WebHookCallbackResponse>,
+) -> StatusCode {
+    for pix in callback.pix {
+        let end_to_end_id = pix.end_to_end_id.clone();
+
+        if let Err(error) = handler.handle(pix).await {
+            tracing::error!(%end_to_end_id, %error, "webhook handler failed");
+            return StatusCode::INTERNAL_SERVER_ERROR;
+        }
+    }
+    StatusCode::OK
+}
+
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(10);
+const MAX_ACCEPT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// HTTPS server of the webhook, accepting only clients authenticated by the PSP's CA.
+pub struct WebHookServer {
+    router: Router,
+    tls_config: Arc<ServerConfig>,
+    handshake_timeout: Duration,
+}
+
+impl WebHookServer {
+    /// `certificate` and `key` are this server's PEM encoded chain and private key, while `psp_ca` contains the PEM
+    /// encoded certificates that sign the PSP's client certificate.
+    pub fn new<H: WebHookHandler>(
+        handler: H,
+        certificate: &[u8],
+        key: &[u8],
+        psp_ca: &[u8],
+    ) -> Result<WebHookServer, WebHookServerError> {
+        let mut client_roots = RootCertStore::empty();
+        for certificate in read_certificates(psp_ca)? {
+            client_roots.add(&certificate)?;
+        }
+
+        let tls_config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_client_cert_verifier(AllowAnyAuthenticatedClient::new(client_roots).boxed())
+            .with_single_cert(read_certificates(certificate)?, read_private_key(key)?)?;
+
+        Ok(Self {
+            router: router(handler),
+            tls_config: Arc::new(tls_config),
+            handshake_timeout: Duration::from_secs(10),
+        })
+    }
+
+    /// How long a client has to complete the TLS handshake before its connection is dropped. Defaults to 10 seconds.
+    pub fn handshake_timeout(mut self, handshake_timeout: Duration) -> WebHookServer {
+        self.handshake_timeout = handshake_timeout;
+        self
+    }
+
+    /// Accepts connections until the returned future is dropped. Connections that fail or don't complete the TLS
+    /// handshake in time, such as the ones without a trusted client certificate, are dropped.
+    ///
+    /// Failures to accept a connection, such as running out of file descriptors, are logged and retried after a
+    /// backoff of up to 1 second.
+    pub async fn serve(self, listener: TcpListener) -> io::Result<()> {
+        let acceptor = TlsAcceptor::from(self.tls_config);
+        let handshake_timeout = self.handshake_timeout;
+        let mut backoff = ACCEPT_BACKOFF;
+
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => {
+                    backoff = ACCEPT_BACKOFF;
+                    accepted
+                }
+                Err(error) => {
+                    tracing::warn!(%error, ?backoff, "failed to accept a webhook connection");
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_ACCEPT_BACKOFF);
+                    continue;
+                }
+            };
+            let acceptor = acceptor.clone();
+            let router = self.router.clone();
+
+            tokio::spawn(async move {
+                let stream = match tokio::time::timeout(handshake_timeout, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => stream,
+                    Ok(Err(error)) => {
+                        tracing::warn!(%peer, %error, "webhook TLS handshake failed");
+                        return;
+                    }
+                    Err(_) => {
+                        tracing::warn!(%peer, "webhook TLS handshake timed out");
+                        return;
+                    }
+                };
+
+                if let Err(error) = Http::new().serve_connection(stream, router).await {
+                    tracing::debug!(%peer, %error, "webhook connection closed");
+                }
+            });
+        }
+    }
+}
+
+fn read_certificates(pem: &[u8]) -> Result<Vec<Certificate>, WebHookServerError> {
+    let certificates = rustls_pemfile::certs(&mut &*pem).map_err(|e| WebHookServerError::InvalidPem(e.to_string()))?;
+
+    if certificates.is_empty() {
+        return Err(WebHookServerError::InvalidPem("no certificate found".to_string()));
+    }
+    Ok(certificates.into_iter().map(Certificate).collect())
+}
+
+fn read_private_key(pem: &[u8]) -> Result<PrivateKey, WebHookServerError> {
+    let items = rustls_pemfile::read_all(&mut &*pem).map_err(|e| WebHookServerError::InvalidPem(e.to_string()))?;
+
+    items
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| WebHookServerError::InvalidPem("no private key found".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use rcgen::{BasicConstraints, Certificate as RcgenCertificate, CertificateParams, DnType, IsCa};
+    use reqwest::{Client, Identity};
+    use serde_json::json;
+    use tokio::io::AsyncReadExt;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::webhook::PixInput;
+
+    fn ca(name: &str) -> RcgenCertificate {
+        let mut params = CertificateParams::new(vec![]);
+        params.distinguished_name.push(DnType::CommonName, name);
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        RcgenCertificate::from_params(params).unwrap()
+    }
+
+    fn signed_by(ca: &RcgenCertificate, name: &str) -> (Vec<u8>, Vec<u8>) {
+        let mut params = CertificateParams::new(vec![name.to_string()]);
+        params.distinguished_name.push(DnType::CommonName, name);
+
+        let certificate = RcgenCertificate::from_params(params).unwrap();
+        (
+            certificate.serialize_pem_with_signer(ca).unwrap().into_bytes(),
+            certificate.serialize_private_key_pem().into_bytes(),
+        )
+    }
+
+    fn callback() -> serde_json::Value {
+        json!({
+            "pix": [{
+                "endToEndId": "E12345678202009091221abcdef12345",
+                "txid": "cd1fe328c875481285a6f233ae41b662",
+                "chave": "7d9f0335-8dcc-4054-9bf9-0dbd61d36906",
+                "valor": "100.00",
+                "horario": "2020-09-10T13:03:33.902Z",
+                "infoPagador": "Reforma da casa"
+            }]
+        })
+    }
+
+    #[tokio::test]
+    async fn t_requires_psp_client_certificate() {
+        let server_ca = ca("Server CA");
+        let psp_ca = ca("PSP CA");
+        let (server_certificate, server_key) = signed_by(&server_ca, "localhost");
+        let (psp_certificate, psp_key) = signed_by(&psp_ca, "psp.example.com");
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let handler = move |pix: PixInput| {
+            let sender = sender.clone();
+            async move {
+                sender.send(pix.end_to_end_id)?;
+                Ok::<_, HandlerError>(())
+            }
+        };
+
+        let server = WebHookServer::new(
+            handler,
+            &server_certificate,
+            &server_key,
+            psp_ca.serialize_pem().unwrap().as_bytes(),
+        )
+        .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("https://localhost:{}/pix", listener.local_addr().unwrap().port());
+        tokio::spawn(server.serve(listener));
+
+        let server_root = reqwest::Certificate::from_pem(server_ca.serialize_pem().unwrap().as_bytes()).unwrap();
+        let psp_client = Client::builder()
+            .add_root_certificate(server_root.clone())
+            .identity(Identity::from_pkcs8_pem(&psp_certificate, &psp_key).unwrap())
+            .build()
+            .unwrap();
+        let anonymous_client = Client::builder().add_root_certificate(server_root).build().unwrap();
+
+        let response = psp_client.post(&url).json(&callback()).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(receiver.recv().await.unwrap(), "E12345678202009091221abcdef12345");
+
+        assert!(anonymous_client.post(&url).json(&callback()).send().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn t_handshake_timeout() {
+        let server_ca = ca("Server CA");
+        let (server_certificate, server_key) = signed_by(&server_ca, "localhost");
+        let handler = |_: PixInput| async { Ok::<_, HandlerError>(()) };
+
+        let server = WebHookServer::new(
+            handler,
+            &server_certificate,
+            &server_key,
+            ca("PSP CA").serialize_pem().unwrap().as_bytes(),
+        )
+        .unwrap()
+        .handshake_timeout(Duration::from_millis(100));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(server.serve(listener));
+
+        // a client that never starts the handshake is disconnected
+        let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+        let mut buffer = [0u8; 16];
+        let read = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buffer)).await;
+        assert!(matches!(read, Ok(Ok(0))));
+    }
+}
@@ -9,9 +9,12 @@ use std::borrow::Cow;
 
 use emv_qrcps::EmvEncoder;
 
-mod json_payload;
+use crate::txid::{Txid, TxidContext, TxidError};
+
+pub mod normalizacao;
 pub mod qr_dinamico;
 pub mod qr_estatico;
+pub mod txid;
 
 #[derive(EmvEncoder, Clone, Debug)]
 pub struct AdditionalData<'a> {
@@ -21,6 +24,23 @@ pub struct AdditionalData<'a> {
     txid: Cow<'a, str>,
 }
 
+impl<'a> AdditionalData<'a> {
+    /// Falha caso `txid` exceda o tamanho aceito pelo BR Code.
+    pub fn new(txid: &'a Txid) -> Result<AdditionalData<'a>, TxidError> {
+        txid.validate(TxidContext::BrCode)?;
+        Ok(Self {
+            txid: txid.as_str().into(),
+        })
+    }
+}
+
+impl Default for AdditionalData<'_> {
+    /// Sem identificador da transação, i.e "***".
+    fn default() -> Self {
+        Self { txid: "***".into() }
+    }
+}
+
 #[derive(EmvEncoder, Clone, Debug)]
 pub struct MerchantAccountInformation<'a> {
     #[encoder(id = "00")]
@@ -45,7 +65,32 @@ pub struct MerchantAccountInformation<'a> {
 }
 
 #[derive(EmvEncoder, Clone, Debug)]
-struct PixSchema<'a> {
+pub struct PixAccountInformation<'a> {
+    #[encoder(id = "00")]
+    pub gui: Cow<'a, str>,
+
+    #[encoder(id = "01")]
+    /// Chave Pix do recebedor
+    pub chave: Option<Cow<'a, str>>,
+
+    #[encoder(id = "25")]
+    /// URL do payload dinâmico
+    pub url: Option<Cow<'a, str>>,
+}
+
+#[derive(EmvEncoder, Clone, Debug)]
+/// Template genérico, usado por outros arranjos presentes no mesmo QR Code.
+pub struct TemplateInformation<'a> {
+    #[encoder(id = "00")]
+    pub gui: Cow<'a, str>,
+
+    #[encoder(id = "01")]
+    pub data: Option<Cow<'a, str>>,
+}
+
+#[derive(EmvEncoder, Clone, Debug)]
+/// BR Code genérico, estático ou dinâmico, que pode conter outros arranjos além do Pix.
+pub struct PixSchema<'a> {
     #[encoder(id = "00")]
     /// Versão do Payload QRCPS-MPM. Default em "01"
     pub format_indicator: Cow<'a, str>,
@@ -54,8 +99,15 @@ struct PixSchema<'a> {
     /// Está presente para indicar que não deve ser iniciado mais de um pagamento com este mesmo QR Code.
     pub point_of_initiation_method: Option<Cow<'a, str>>,
 
+    #[encoder(id = "04")]
+    /// Reservado para outros arranjos de pagamento.
+    pub reserved_account_information: Option<Cow<'a, str>>,
+
     #[encoder(id = "26")]
-    pub merchant_account_information: MerchantAccountInformation<'a>,
+    pub merchant_account_information: PixAccountInformation<'a>,
+
+    #[encoder(id = "27")]
+    pub other_account_information: Option<TemplateInformation<'a>>,
 
     #[encoder(id = "52")]
     pub merchant_category_code: Cow<'a, str>,
@@ -79,8 +131,12 @@ struct PixSchema<'a> {
     #[encoder(id = "61")]
     /// Postal Code
     pub postal_code: Option<Cow<'a, str>>,
-    /* #[encoder(id = "62")]
-     * pub additional_data: Option<AdditionalData<'a>>, */
+
+    #[encoder(id = "62")]
+    pub additional_data: Option<AdditionalData<'a>>,
+
+    #[encoder(id = "80")]
+    pub unreserved_template: Option<TemplateInformation<'a>>,
 }
 
 #[cfg(test)]
@@ -0,0 +1,126 @@
+//! Normalização do nome (tag 59) e da cidade (tag 60) do recebedor.
+//!
+//! O BR Code limita o nome a 25 e a cidade a 15 bytes, e os apps dos pagadores exibem acentos de forma
+//! inconsistente. [`Normalizer`] translitera os diacríticos, remove caracteres fora do ASCII imprimível e trunca o
+//! valor no último espaço que caiba no limite.
+//!
+//! # Example
+//!
+//! ```
+//! use pix_brcode::normalizacao::Normalizer;
+//!
+//! let normalizer = Normalizer::new();
+//! assert_eq!(normalizer.merchant_name("Padaria São João da Boa Vista"), "Padaria Sao Joao da Boa");
+//! assert_eq!(normalizer.uppercase(true).merchant_city("São José dos Campos"), "SAO JOSE DOS");
+//! ```
+
+pub const MAX_MERCHANT_NAME: usize = 25;
+pub const MAX_MERCHANT_CITY: usize = 15;
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Normalizer {
+    uppercase: bool,
+}
+
+impl Normalizer {
+    pub fn new() -> Normalizer {
+        Self::default()
+    }
+
+    /// Converte o resultado para maiúsculas. Desabilitado por padrão.
+    pub fn uppercase(mut self, uppercase: bool) -> Normalizer {
+        self.uppercase = uppercase;
+        self
+    }
+
+    pub fn merchant_name(&self, merchant_name: &str) -> String {
+        self.normalize(merchant_name, MAX_MERCHANT_NAME)
+    }
+
+    pub fn merchant_city(&self, merchant_city: &str) -> String {
+        self.normalize(merchant_city, MAX_MERCHANT_CITY)
+    }
+
+    /// Translitera, remove caracteres não permitidos, unifica espaços e trunca `value` em até `max` caracteres.
+    /// Uma única palavra maior que `max` é cortada no limite.
+    pub fn normalize(&self, value: &str, max: usize) -> String {
+        let ascii: String = value
+            .chars()
+            .map(transliterate)
+            .filter(|c| matches!(c, ' '..='~'))
+            .map(|c| if self.uppercase { c.to_ascii_uppercase() } else { c })
+            .collect();
+        let normalized = ascii.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        if normalized.len() <= max {
+            return normalized;
+        }
+
+        // `normalized` contém somente ASCII, então cada byte é um caractere
+        let cut = match normalized.as_bytes()[max] {
+            b' ' => max,
+            _ => normalized[..max].rfind(' ').unwrap_or(max),
+        };
+        truncate(&normalized, cut).trim_end().to_string()
+    }
+}
+
+/// Trunca `value` em até `max` bytes, sem dividir um caractere.
+pub fn truncate(value: &str, max: usize) -> &str {
+    if value.len() <= max {
+        return value;
+    }
+
+    let end = (0..=max).rev().find(|&i| value.is_char_boundary(i)).unwrap_or(0);
+    &value[..end]
+}
+
+/// Remove o diacrítico de `c`, e converte espaços Unicode em espaços comuns.
+fn transliterate(c: char) -> char {
+    match c {
+        'á' | 'à' | 'â' | 'ã' | 'ä' | 'å' => 'a',
+        'Á' | 'À' | 'Â' | 'Ã' | 'Ä' | 'Å' => 'A',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'É' | 'È' | 'Ê' | 'Ë' => 'E',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'Í' | 'Ì' | 'Î' | 'Ï' => 'I',
+        'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+        'Ó' | 'Ò' | 'Ô' | 'Õ' | 'Ö' => 'O',
+        'ú' | 'ù' | 'û' | 'ü' => 'u',
+        'Ú' | 'Ù' | 'Û' | 'Ü' => 'U',
+        'ç' => 'c',
+        'Ç' => 'C',
+        'ñ' => 'n',
+        'Ñ' => 'N',
+        'ý' | 'ÿ' => 'y',
+        'Ý' => 'Y',
+        c if c.is_whitespace() => ' ',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_normalize() {
+        let normalizer = Normalizer::new();
+
+        assert_eq!(normalizer.merchant_name("Açaí & Café   Ltda."), "Acai & Cafe Ltda.");
+        assert_eq!(normalizer.merchant_name("Loja\u{a0}do Zé ✓"), "Loja do Ze");
+        assert_eq!(normalizer.merchant_city("Brasília"), "Brasilia");
+        assert_eq!(normalizer.merchant_city("Florianópolis-SC"), "Florianopolis-S");
+        assert_eq!(normalizer.merchant_city("Rio de Janeiro RJ"), "Rio de Janeiro");
+        assert_eq!(normalizer.uppercase(true).merchant_city("Gonçalves"), "GONCALVES");
+    }
+
+    #[test]
+    fn t_truncate() {
+        assert_eq!(truncate("Brasilia", MAX_MERCHANT_CITY), "Brasilia");
+        assert_eq!(truncate("Sao Jose dos Campos", MAX_MERCHANT_CITY), "Sao Jose dos Ca");
+        // "ã" ocupa 2 bytes, e não cabe inteiro no limite
+        assert_eq!(truncate("São José dos Campos", MAX_MERCHANT_CITY), "São José dos ");
+        assert_eq!(truncate("ãã", 3), "ã");
+    }
+}
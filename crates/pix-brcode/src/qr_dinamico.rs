@@ -1,6 +1,12 @@
 use std::borrow::Cow;
 
 use emv_qrcps::EmvEncoder;
+use thiserror::Error;
+
+use crate::normalizacao::{Normalizer, MAX_MERCHANT_CITY, MAX_MERCHANT_NAME};
+
+/// Tamanho máximo da url da location, tag 26-25.
+pub const MAX_LOCATION: usize = 77;
 
 #[derive(EmvEncoder, Clone, Debug)]
 pub struct AdditionalData<'a> {
@@ -68,6 +74,28 @@ pub struct PixDinamicoSchema<'a> {
 }
 
 impl<'a> PixDinamicoSchema<'a> {
+    /// Builder validado, ao contrário de [`PixDinamicoSchema::standard`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use pix_brcode::qr_dinamico::PixDinamicoSchema;
+    ///
+    /// # fn main() -> Result<(), pix_brcode::qr_dinamico::PixDinamicoError> {
+    /// let brcode = PixDinamicoSchema::builder()
+    ///     .location("pix.example.com/qr/9d36b84fc70b478fb95c12729b90ca25")
+    ///     .amount("567.89")
+    ///     .merchant("Padaria São João", "Brasília")
+    ///     .postal_code("70074900")
+    ///     .build()?
+    ///     .serialize_with_src();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn builder() -> PixDinamicoBuilder<'a> {
+        PixDinamicoBuilder::default()
+    }
+
     /// Creates the most basic version of the QR Code, with every possible field with its default.
     pub fn standard<MA, MC, TA, L>(merchant_name: MA, merchant_city: MC, transaction_amount: TA, location: L) -> Self
     where
@@ -99,3 +127,245 @@ impl<'a> PixDinamicoSchema<'a> {
         }
     }
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PixDinamicoError {
+    #[error("location is mandatory")]
+    MissingLocation,
+    #[error("merchant name and city are mandatory")]
+    MissingMerchant,
+    #[error("location must have up to {MAX_LOCATION} characters and no scheme, found {0:?}")]
+    InvalidLocation(String),
+    #[error("amount must be a positive value with up to 2 decimal places and 13 characters, found {0:?}")]
+    InvalidAmount(String),
+    #[error("merchant name must have from 1 to {MAX_MERCHANT_NAME} printable ASCII characters, found {0:?}")]
+    InvalidMerchantName(String),
+    #[error("merchant city must have from 1 to {MAX_MERCHANT_CITY} printable ASCII characters, found {0:?}")]
+    InvalidMerchantCity(String),
+    #[error("merchant category code must have 4 digits, found {0:?}")]
+    InvalidMerchantCategoryCode(String),
+    #[error("postal code must have 8 digits, found {0:?}")]
+    InvalidPostalCode(String),
+}
+
+/// Builder de [`PixDinamicoSchema`], criado por [`PixDinamicoSchema::builder`].
+///
+/// Nome e cidade do recebedor são normalizados com [`Normalizer`], a não ser que `normalize(false)` seja informado.
+#[derive(Debug, Clone)]
+pub struct PixDinamicoBuilder<'a> {
+    location: Option<Cow<'a, str>>,
+    amount: Option<Cow<'a, str>>,
+    merchant: Option<(Cow<'a, str>, Cow<'a, str>)>,
+    merchant_category_code: Cow<'a, str>,
+    postal_code: Option<Cow<'a, str>>,
+    reusable: bool,
+    normalizer: Option<Normalizer>,
+}
+
+impl Default for PixDinamicoBuilder<'_> {
+    fn default() -> Self {
+        Self {
+            location: None,
+            amount: None,
+            merchant: None,
+            merchant_category_code: "0000".into(),
+            postal_code: None,
+            reusable: false,
+            normalizer: Some(Normalizer::new()),
+        }
+    }
+}
+
+impl<'a> PixDinamicoBuilder<'a> {
+    /// Url da location, sem o prefixo `https://`.
+    pub fn location<L: Into<Cow<'a, str>>>(mut self, location: L) -> Self {
+        self.location = Some(location.into());
+        self
+    }
+
+    /// Valor no formato `123.45`. Omitido por padrão, quando o valor é obtido somente pela location.
+    pub fn amount<A: Into<Cow<'a, str>>>(mut self, amount: A) -> Self {
+        self.amount = Some(amount.into());
+        self
+    }
+
+    pub fn merchant<N, C>(mut self, name: N, city: C) -> Self
+    where
+        N: Into<Cow<'a, str>>,
+        C: Into<Cow<'a, str>>,
+    {
+        self.merchant = Some((name.into(), city.into()));
+        self
+    }
+
+    /// Defaults to "0000".
+    pub fn merchant_category_code<M: Into<Cow<'a, str>>>(mut self, merchant_category_code: M) -> Self {
+        self.merchant_category_code = merchant_category_code.into();
+        self
+    }
+
+    /// CEP do recebedor, com 8 dígitos.
+    pub fn postal_code<P: Into<Cow<'a, str>>>(mut self, postal_code: P) -> Self {
+        self.postal_code = Some(postal_code.into());
+        self
+    }
+
+    /// Uma location reutilizável pode ser paga mais de uma vez, e omite o `point_of_initiation_method` `12`.
+    /// Desabilitado por padrão.
+    pub fn reusable(mut self, reusable: bool) -> Self {
+        self.reusable = reusable;
+        self
+    }
+
+    /// Normaliza nome e cidade do recebedor. Habilitado por padrão.
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.normalizer = if normalize { Some(Normalizer::new()) } else { None };
+        self
+    }
+
+    /// Normaliza nome e cidade do recebedor com `normalizer`, por exemplo para convertê-los em maiúsculas.
+    pub fn normalizer(mut self, normalizer: Normalizer) -> Self {
+        self.normalizer = Some(normalizer);
+        self
+    }
+
+    pub fn build(self) -> Result<PixDinamicoSchema<'a>, PixDinamicoError> {
+        let location = self.location.ok_or(PixDinamicoError::MissingLocation)?;
+        if location.is_empty() || location.len() > MAX_LOCATION || location.contains("://") || !is_printable(&location)
+        {
+            return Err(PixDinamicoError::InvalidLocation(location.into_owned()));
+        }
+
+        if let Some(amount) = &self.amount {
+            if !is_valid_amount(amount) {
+                return Err(PixDinamicoError::InvalidAmount(amount.to_string()));
+            }
+        }
+
+        let (name, city) = self.merchant.ok_or(PixDinamicoError::MissingMerchant)?;
+        let (name, city): (Cow<'a, str>, Cow<'a, str>) = match self.normalizer {
+            Some(normalizer) => (
+                normalizer.merchant_name(&name).into(),
+                normalizer.merchant_city(&city).into(),
+            ),
+            None => (name, city),
+        };
+        if name.is_empty() || name.len() > MAX_MERCHANT_NAME || !is_printable(&name) {
+            return Err(PixDinamicoError::InvalidMerchantName(name.into_owned()));
+        }
+        if city.is_empty() || city.len() > MAX_MERCHANT_CITY || !is_printable(&city) {
+            return Err(PixDinamicoError::InvalidMerchantCity(city.into_owned()));
+        }
+
+        let mcc = self.merchant_category_code;
+        if mcc.len() != 4 || !mcc.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(PixDinamicoError::InvalidMerchantCategoryCode(mcc.into_owned()));
+        }
+
+        if let Some(postal_code) = &self.postal_code {
+            if postal_code.len() != 8 || !postal_code.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(PixDinamicoError::InvalidPostalCode(postal_code.to_string()));
+            }
+        }
+
+        let mut schema = PixDinamicoSchema::standard(name, city, "", location);
+        schema.point_of_initiation_method = if self.reusable { None } else { Some("12".into()) };
+        schema.merchant_category_code = mcc;
+        schema.transaction_amount = self.amount;
+        schema.postal_code = self.postal_code;
+        Ok(schema)
+    }
+}
+
+fn is_printable(value: &str) -> bool {
+    value.chars().all(|c| matches!(c, ' '..='~'))
+}
+
+/// `\d+(\.\d{1,2})?`, positivo e com até 13 caracteres.
+fn is_valid_amount(amount: &str) -> bool {
+    let (integer, decimals) = amount.split_once('.').unwrap_or((amount, ""));
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+
+    amount.len() <= 13
+        && !integer.is_empty()
+        && digits(integer)
+        && decimals.len() <= 2
+        && digits(decimals)
+        && !(amount.contains('.') && decimals.is_empty())
+        && amount.bytes().any(|b| (b'1'..=b'9').contains(&b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCATION: &str = "pix.example.com/qr/9d36b84fc70b478fb95c12729b90ca25";
+
+    #[test]
+    fn t_builder() {
+        let schema = PixDinamicoSchema::builder()
+            .location(LOCATION)
+            .amount("10.5")
+            .merchant("Açaí do Zé", "São Paulo")
+            .postal_code("01310100")
+            .build()
+            .unwrap();
+
+        assert_eq!(schema.merchant_name, "Acai do Ze");
+        assert_eq!(schema.merchant_city, "Sao Paulo");
+        assert_eq!(schema.point_of_initiation_method.as_deref(), Some("12"));
+        assert_eq!(schema.transaction_amount.as_deref(), Some("10.5"));
+
+        let reusable = PixDinamicoSchema::builder()
+            .location(LOCATION)
+            .merchant("Loja", "Cidade")
+            .reusable(true)
+            .build()
+            .unwrap();
+        assert_eq!(reusable.point_of_initiation_method, None);
+        assert_eq!(reusable.transaction_amount, None);
+    }
+
+    #[test]
+    fn t_builder_validation() {
+        let builder = || {
+            PixDinamicoSchema::builder()
+                .location(LOCATION)
+                .merchant("Loja", "Cidade")
+        };
+
+        assert_eq!(
+            PixDinamicoSchema::builder()
+                .merchant("Loja", "Cidade")
+                .build()
+                .unwrap_err(),
+            PixDinamicoError::MissingLocation
+        );
+        assert_eq!(
+            PixDinamicoSchema::builder().location(LOCATION).build().unwrap_err(),
+            PixDinamicoError::MissingMerchant
+        );
+        assert!(matches!(
+            builder().location(format!("https://{}", LOCATION)).build(),
+            Err(PixDinamicoError::InvalidLocation(_))
+        ));
+        for amount in ["0.00", "10.", "1.234", "-5", "1e3", "12345678901.00"] {
+            assert_eq!(
+                builder().amount(amount).build().unwrap_err(),
+                PixDinamicoError::InvalidAmount(amount.to_string())
+            );
+        }
+        assert!(matches!(
+            builder().merchant("Loja", "São Paulo").normalize(false).build(),
+            Err(PixDinamicoError::InvalidMerchantCity(_))
+        ));
+        assert!(matches!(
+            builder().merchant("", "Cidade").build(),
+            Err(PixDinamicoError::InvalidMerchantName(_))
+        ));
+        assert!(matches!(
+            builder().postal_code("01310-100").build(),
+            Err(PixDinamicoError::InvalidPostalCode(_))
+        ));
+    }
+}
@@ -0,0 +1,296 @@
+//! Identificador da transação (`txid`), que associa um Pix recebido à sua cobrança.
+//!
+//! Cobranças criadas na API Pix exigem de 26 a 35 caracteres alfanuméricos, enquanto o campo `txid` do BR Code
+//! estático aceita no máximo 25.
+//!
+//! # Example
+//!
+//! ```
+//! use std::convert::TryFrom;
+//! use pix_brcode::txid::{CobTxid, Txid, TxidContext};
+//!
+//! let aleatorio = Txid::random(TxidContext::Cob);
+//! assert_eq!(aleatorio.as_str().len(), 35);
+//!
+//! // o mesmo pedido gera sempre o mesmo txid, tornando a criação da cobrança idempotente
+//! let pedido = Txid::from_reference("pedido-4815", TxidContext::Cob);
+//! assert_eq!(pedido, Txid::from_reference("pedido-4815", TxidContext::Cob));
+//!
+//! assert!(Txid::new("curto", TxidContext::Cob).is_err());
+//! assert!(Txid::new("curto", TxidContext::BrCode).is_ok());
+//!
+//! // já validado para cobranças da API Pix
+//! let cob = CobTxid::try_from(pedido).unwrap();
+//! assert!(CobTxid::new("curto").is_err());
+//! ```
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Onde o `txid` será utilizado, o que define seu tamanho aceito.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TxidContext {
+    /// Cobranças imediatas e com vencimento da API Pix, com 26 a 35 caracteres.
+    Cob,
+    /// Campo `05` do BR Code estático, com 1 a 25 caracteres.
+    BrCode,
+}
+
+impl TxidContext {
+    pub fn length(self) -> RangeInclusive<usize> {
+        match self {
+            TxidContext::Cob => 26..=35,
+            TxidContext::BrCode => 1..=25,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TxidError {
+    #[error("txid must have from {min} to {max} characters, but has {length}")]
+    InvalidLength { length: usize, min: usize, max: usize },
+    #[error("txid must be alphanumeric, found {0:?}")]
+    InvalidCharacter(char),
+}
+
+/// Um `txid` validado, contendo somente `[a-zA-Z0-9]`.
+///
+/// Ao ser desserializado, é aceito o tamanho de qualquer [`TxidContext`], de 1 a 35 caracteres.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Txid(String);
+
+impl Txid {
+    pub fn new<T: Into<String>>(txid: T, context: TxidContext) -> Result<Txid, TxidError> {
+        let txid = Txid(txid.into());
+        txid.validate(context)?;
+        Ok(txid)
+    }
+
+    /// Gerado com o gerador aleatório seguro do sistema operacional, com o tamanho máximo de `context`.
+    ///
+    /// # Panics
+    ///
+    /// Caso o sistema operacional não forneça números aleatórios.
+    pub fn random(context: TxidContext) -> Txid {
+        let length = *context.length().end();
+        let mut txid = String::with_capacity(length);
+        let mut bytes = [0u8; 64];
+
+        while txid.len() < length {
+            getrandom::getrandom(&mut bytes).expect("the OS random number generator is unavailable");
+
+            // descarta os bytes acima do maior múltiplo de 62, evitando viés
+            let limit = (256 / ALPHABET.len() * ALPHABET.len()) as u8;
+            txid.extend(
+                bytes
+                    .iter()
+                    .filter(|&&b| b < limit)
+                    .map(|&b| ALPHABET[b as usize % ALPHABET.len()] as char)
+                    .take(length - txid.len()),
+            );
+        }
+
+        Txid(txid)
+    }
+
+    /// Derivado do SHA-256 de `reference`, como o id de um pedido, de forma que a mesma referência resulte sempre
+    /// no mesmo `txid`.
+    pub fn from_reference(reference: &str, context: TxidContext) -> Txid {
+        let digest = Sha256::digest(reference.as_bytes());
+        let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+
+        Txid(hex[..*context.length().end()].to_string())
+    }
+
+    pub fn validate(&self, context: TxidContext) -> Result<(), TxidError> {
+        self.validate_length(context.length())
+    }
+
+    fn validate_length(&self, length: RangeInclusive<usize>) -> Result<(), TxidError> {
+        if let Some(c) = self.0.chars().find(|c| !c.is_ascii_alphanumeric()) {
+            return Err(TxidError::InvalidCharacter(c));
+        }
+
+        if !length.contains(&self.0.len()) {
+            return Err(TxidError::InvalidLength {
+                length: self.0.len(),
+                min: *length.start(),
+                max: *length.end(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Txid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for Txid {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for Txid {
+    type Error = TxidError;
+
+    fn try_from(txid: String) -> Result<Txid, TxidError> {
+        let txid = Txid(txid);
+        txid.validate_length(*TxidContext::BrCode.length().start()..=*TxidContext::Cob.length().end())?;
+        Ok(txid)
+    }
+}
+
+impl From<Txid> for String {
+    fn from(txid: Txid) -> String {
+        txid.0
+    }
+}
+
+/// Um [`Txid`] válido em [`TxidContext::Cob`], aceito pelas cobranças imediatas e com vencimento da API Pix.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CobTxid(Txid);
+
+impl CobTxid {
+    pub fn new<T: Into<String>>(txid: T) -> Result<CobTxid, TxidError> {
+        Txid::new(txid, TxidContext::Cob).map(CobTxid)
+    }
+
+    /// Ver [`Txid::random`].
+    pub fn random() -> CobTxid {
+        CobTxid(Txid::random(TxidContext::Cob))
+    }
+
+    /// Ver [`Txid::from_reference`].
+    pub fn from_reference(reference: &str) -> CobTxid {
+        CobTxid(Txid::from_reference(reference, TxidContext::Cob))
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl fmt::Display for CobTxid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl AsRef<str> for CobTxid {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl TryFrom<Txid> for CobTxid {
+    type Error = TxidError;
+
+    fn try_from(txid: Txid) -> Result<CobTxid, TxidError> {
+        txid.validate(TxidContext::Cob)?;
+        Ok(CobTxid(txid))
+    }
+}
+
+impl TryFrom<String> for CobTxid {
+    type Error = TxidError;
+
+    fn try_from(txid: String) -> Result<CobTxid, TxidError> {
+        CobTxid::new(txid)
+    }
+}
+
+impl From<CobTxid> for Txid {
+    fn from(txid: CobTxid) -> Txid {
+        txid.0
+    }
+}
+
+impl From<CobTxid> for String {
+    fn from(txid: CobTxid) -> String {
+        txid.0.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_validacao() {
+        assert!(Txid::new("7978c0c97ea847e78e8849634473c1f1", TxidContext::Cob).is_ok());
+        assert_eq!(
+            Txid::new("7978c0c97ea847e78e8849634473c1f1", TxidContext::BrCode),
+            Err(TxidError::InvalidLength {
+                length: 32,
+                min: 1,
+                max: 25
+            })
+        );
+        assert_eq!(
+            Txid::new("RP12345678-2019", TxidContext::BrCode),
+            Err(TxidError::InvalidCharacter('-'))
+        );
+        assert!(Txid::new("", TxidContext::BrCode).is_err());
+    }
+
+    #[test]
+    fn t_serde() {
+        let txid: Txid = serde_json::from_str(r#""7978c0c97ea847e78e8849634473c1f1""#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&txid).unwrap(),
+            r#""7978c0c97ea847e78e8849634473c1f1""#
+        );
+        assert!(serde_json::from_str::<Txid>(r#""RP12345678""#).is_ok());
+
+        assert!(serde_json::from_str::<Txid>(r#""RP12345678-2019""#).is_err());
+        assert!(serde_json::from_str::<Txid>(r#""""#).is_err());
+        assert!(serde_json::from_str::<Txid>(&format!(r#""{}""#, "a".repeat(36))).is_err());
+    }
+
+    #[test]
+    fn t_cob_txid() {
+        assert!(CobTxid::new("7978c0c97ea847e78e8849634473c1f1").is_ok());
+        assert!(matches!(
+            CobTxid::new("RP12345678"),
+            Err(TxidError::InvalidLength { length: 10, .. })
+        ));
+
+        let curto = Txid::new("RP12345678", TxidContext::BrCode).unwrap();
+        assert!(CobTxid::try_from(curto).is_err());
+        assert!(serde_json::from_str::<CobTxid>(r#""RP12345678""#).is_err());
+
+        let cob = CobTxid::random();
+        assert!(Txid::from(cob.clone()).validate(TxidContext::Cob).is_ok());
+        assert_eq!(CobTxid::from_reference("pedido-4815").as_str().len(), 35);
+    }
+
+    #[test]
+    fn t_geradores() {
+        for context in [TxidContext::Cob, TxidContext::BrCode] {
+            let random = Txid::random(context);
+            assert!(random.validate(context).is_ok());
+            assert_ne!(random, Txid::random(context));
+
+            let reference = Txid::from_reference("pedido-4815", context);
+            assert!(reference.validate(context).is_ok());
+            assert_eq!(reference, Txid::from_reference("pedido-4815", context));
+            assert_ne!(reference, Txid::from_reference("pedido-4816", context));
+        }
+    }
+}